@@ -0,0 +1,463 @@
+use crate::{
+    input::Input,
+    output::{optional, table},
+};
+use arrow_array::{
+    cast::AsArray,
+    types::{
+        Decimal128Type, Float16Type, Float32Type, Float64Type, Int32Type, Int64Type, UInt32Type,
+        UInt64Type,
+    },
+    StringArray,
+};
+use arrow_cast::{cast, cast_with_options, CastOptions};
+use arrow_schema::DataType;
+use comfy_table::Cell;
+use parquet::{
+    arrow::{parquet_to_arrow_schema_by_columns, ProjectionMask},
+    basic::Type,
+    errors::{ParquetError, Result},
+    file::{
+        metadata::ParquetMetaData, properties::ReaderProperties, reader::RowGroupReader,
+        serialized_reader::SerializedRowGroupReader,
+    },
+    format::BloomFilterHeader,
+    schema::types::SchemaDescriptor,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Where the bloom filter of a column chunk is stored.
+struct Filter {
+    row_group: usize,
+    column: String,
+    offset: Option<i64>,
+    /// Read from the header of the filter when the metadata doesn't have it.
+    length: Option<u64>,
+}
+
+/// Finds the bloom filters of the specified leaf columns.
+fn collect(input: &Input, metadata: &ParquetMetaData, leaves: &[usize]) -> Result<Vec<Filter>> {
+    let mut filters = vec![];
+    for (i, rg) in metadata.row_groups().iter().enumerate() {
+        for c in leaves.iter().map(|&j| rg.column(j)) {
+            let offset = c.bloom_filter_offset();
+            let length = match (offset, c.bloom_filter_length()) {
+                (_, Some(length)) => Some(length as u64),
+                (Some(offset), None) => {
+                    let (header, len) = input
+                        .read_thrift::<BloomFilterHeader>(offset as u64, "bloom filter header")?;
+                    Some(len + header.num_bytes.max(0) as u64)
+                }
+                (None, None) => None,
+            };
+            filters.push(Filter {
+                row_group: i,
+                column: c.column_path().string(),
+                offset,
+                length,
+            });
+        }
+    }
+    Ok(filters)
+}
+
+/// Prints the bloom filters of the specified leaf columns.
+pub fn print(
+    input: &Input,
+    metadata: &ParquetMetaData,
+    leaves: &[usize],
+    fit_width: Option<usize>,
+) -> Result<()> {
+    let rows = collect(input, metadata, leaves)?.into_iter().map(|f| {
+        vec![
+            Cell::new(f.row_group),
+            Cell::new(f.column),
+            Cell::new(optional(f.offset)),
+            Cell::new(optional(f.length)),
+        ]
+    });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec!["row group", "column", "offset", "length"])
+            .add_rows(rows)
+    );
+    Ok(())
+}
+
+/// Converts the bloom filters of the specified leaf columns to JSON.
+pub fn to_json(input: &Input, metadata: &ParquetMetaData, leaves: &[usize]) -> Result<Value> {
+    Ok(collect(input, metadata, leaves)?
+        .into_iter()
+        .map(|f| {
+            json!({
+                "row_group": f.row_group,
+                "column": f.column,
+                "offset": f.offset,
+                "length": f.length,
+            })
+        })
+        .collect())
+}
+
+/// A value to look up in the bloom filters of a column.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    column: String,
+    value: String,
+}
+
+/// Parses a probe in the `COLUMN=VALUE` form.
+pub fn parse_probe(s: &str) -> Result<Probe, String> {
+    let (column, value) = s
+        .split_once('=')
+        .ok_or_else(|| "expected COLUMN=VALUE".to_string())?;
+    Ok(Probe {
+        column: column.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Whether a row group might contain a probed value. The result is `None`
+/// when the column chunk has no bloom filter.
+struct Check<'a> {
+    row_group: usize,
+    probe: &'a Probe,
+    result: Option<bool>,
+}
+
+/// Checks each probe against the bloom filters of every row group.
+fn check<'a>(
+    input: &Input,
+    metadata: &ParquetMetaData,
+    probes: &'a [Probe],
+) -> Result<Vec<Check<'a>>> {
+    let schema = metadata.file_metadata().schema_descr();
+    let probes = probes
+        .iter()
+        .map(|probe| {
+            let leaf = (0..schema.num_columns())
+                .find(|&j| schema.column(j).path().string() == probe.column)
+                .ok_or_else(|| ParquetError::General(format!("no column `{}`", probe.column)))?;
+            Ok((probe, leaf, encode(schema, leaf, &probe.value)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let props = Arc::new(
+        ReaderProperties::builder()
+            .set_read_bloom_filter(true)
+            .build(),
+    );
+    let input = Arc::new(input.clone());
+    let mut checks = vec![];
+    for (i, rg) in metadata.row_groups().iter().enumerate() {
+        let reader = SerializedRowGroupReader::new(input.clone(), rg, None, props.clone())?;
+        for (probe, leaf, bytes) in &probes {
+            checks.push(Check {
+                row_group: i,
+                probe,
+                result: reader
+                    .get_column_bloom_filter(*leaf)
+                    .map(|f| f.check(bytes)),
+            });
+        }
+    }
+    Ok(checks)
+}
+
+/// Encodes `value` the way the leaf column stores it, which is what bloom
+/// filters hash. The value is parsed as the Arrow type of the column, so
+/// dates, timestamps and decimals are written as they are displayed.
+fn encode(schema: &SchemaDescriptor, leaf: usize, value: &str) -> Result<Vec<u8>> {
+    let descr = schema.column(leaf);
+    // Maps can't be projected to a single leaf, so the whole column is.
+    let root = schema.get_column_root_idx(leaf);
+    let first = (0..leaf)
+        .find(|&j| schema.get_column_root_idx(j) == root)
+        .unwrap_or(leaf);
+    let arrow =
+        parquet_to_arrow_schema_by_columns(schema, ProjectionMask::roots(schema, [root]), None)?;
+    let mut leaves = vec![];
+    leaf_types(arrow.field(0).data_type(), &mut leaves);
+    let data_type = leaves.swap_remove(leaf - first);
+    let target = match data_type {
+        DataType::FixedSizeBinary(_) => DataType::Binary,
+        ref t => t.clone(),
+    };
+    let options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    let array =
+        cast_with_options(&StringArray::from(vec![value]), &target, &options).map_err(|e| {
+            ParquetError::General(format!(
+                "can't parse `{}` as {} for column `{}`: {}",
+                value,
+                data_type,
+                descr.path().string(),
+                e
+            ))
+        })?;
+    Ok(match (descr.physical_type(), &data_type) {
+        (Type::INT32, DataType::UInt32) => (array.as_primitive::<UInt32Type>().value(0) as i32)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::INT32, DataType::Decimal128(..)) => (array.as_primitive::<Decimal128Type>().value(0)
+            as i32)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::INT32, _) => cast(&array, &DataType::Int32)?
+            .as_primitive::<Int32Type>()
+            .value(0)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::INT64, DataType::UInt64) => (array.as_primitive::<UInt64Type>().value(0) as i64)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::INT64, DataType::Decimal128(..)) => (array.as_primitive::<Decimal128Type>().value(0)
+            as i64)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::INT64, _) => cast(&array, &DataType::Int64)?
+            .as_primitive::<Int64Type>()
+            .value(0)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::FLOAT, _) => cast(&array, &DataType::Float32)?
+            .as_primitive::<Float32Type>()
+            .value(0)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::DOUBLE, _) => cast(&array, &DataType::Float64)?
+            .as_primitive::<Float64Type>()
+            .value(0)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::BYTE_ARRAY, _) => cast(&array, &DataType::Binary)?
+            .as_binary::<i32>()
+            .value(0)
+            .to_vec(),
+        (Type::FIXED_LEN_BYTE_ARRAY, DataType::Decimal128(..)) => {
+            let len = descr.type_length() as usize;
+            array
+                .as_primitive::<Decimal128Type>()
+                .value(0)
+                .to_be_bytes()[16 - len..]
+                .to_vec()
+        }
+        (Type::FIXED_LEN_BYTE_ARRAY, DataType::Float16) => array
+            .as_primitive::<Float16Type>()
+            .value(0)
+            .to_le_bytes()
+            .to_vec(),
+        (Type::FIXED_LEN_BYTE_ARRAY, DataType::FixedSizeBinary(len)) => {
+            let bytes = array.as_binary::<i32>().value(0);
+            if bytes.len() != *len as usize {
+                return Err(ParquetError::General(format!(
+                    "`{}` isn't {} bytes long as column `{}` is",
+                    value,
+                    len,
+                    descr.path().string()
+                )));
+            }
+            bytes.to_vec()
+        }
+        (physical, _) => {
+            return Err(ParquetError::General(format!(
+                "bloom filters of {} columns can't be probed",
+                physical
+            )))
+        }
+    })
+}
+
+/// The types of the leaves of a nested type, in the order of the Parquet
+/// leaf columns.
+fn leaf_types(data_type: &DataType, leaves: &mut Vec<DataType>) {
+    match data_type {
+        DataType::Struct(fields) => {
+            for field in fields {
+                leaf_types(field.data_type(), leaves);
+            }
+        }
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::FixedSizeList(field, _)
+        | DataType::Map(field, _) => leaf_types(field.data_type(), leaves),
+        t => leaves.push(t.clone()),
+    }
+}
+
+/// Prints whether each row group might contain the probed values.
+pub fn print_check(
+    input: &Input,
+    metadata: &ParquetMetaData,
+    probes: &[Probe],
+    fit_width: Option<usize>,
+) -> Result<()> {
+    let rows = check(input, metadata, probes)?.into_iter().map(|c| {
+        vec![
+            Cell::new(c.row_group),
+            Cell::new(&c.probe.column),
+            Cell::new(&c.probe.value),
+            Cell::new(match c.result {
+                Some(true) => "might contain",
+                Some(false) => "doesn't contain",
+                None => "no bloom filter",
+            }),
+        ]
+    });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec!["row group", "column", "value", "bloom filter"])
+            .add_rows(rows)
+    );
+    Ok(())
+}
+
+/// Converts whether each row group might contain the probed values to JSON.
+pub fn check_to_json(input: &Input, metadata: &ParquetMetaData, probes: &[Probe]) -> Result<Value> {
+    Ok(check(input, metadata, probes)?
+        .into_iter()
+        .map(|c| {
+            json!({
+                "row_group": c.row_group,
+                "column": c.probe.column,
+                "value": c.probe.value,
+                "might_contain": c.result,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use arrow_array::{
+        ArrayRef, Date32Array, Decimal128Array, Int32Array, Int64Array, RecordBatch, UInt32Array,
+    };
+    use parquet::{
+        arrow::arrow_reader::ParquetRecordBatchReaderBuilder, file::properties::WriterProperties,
+    };
+
+    /// A file of 100 rows with bloom filters, holding the values of `i`
+    /// from 0 to 99 as each type.
+    fn file() -> (Input, Arc<ParquetMetaData>) {
+        let decimal = |precision| {
+            Arc::new(
+                Decimal128Array::from_iter_values((0..100).map(|i| i * 100 + 25))
+                    .with_precision_and_scale(precision, 2)
+                    .unwrap(),
+            ) as ArrayRef
+        };
+        let batch = RecordBatch::try_from_iter([
+            (
+                "i32",
+                Arc::new(Int32Array::from_iter_values(0..100)) as ArrayRef,
+            ),
+            (
+                "u32",
+                Arc::new(UInt32Array::from_iter_values(
+                    (0..100).map(|i| u32::MAX - i),
+                )),
+            ),
+            (
+                "i64",
+                Arc::new(Int64Array::from_iter_values((0..100).map(|i| i << 40))),
+            ),
+            (
+                "utf8",
+                Arc::new(StringArray::from_iter_values(
+                    (0..100).map(|i| format!("v{}", i)),
+                )),
+            ),
+            ("dec9", decimal(9)),
+            ("dec18", decimal(18)),
+            ("dec38", decimal(38)),
+            (
+                "date",
+                Arc::new(Date32Array::from_iter_values(19000..19100)),
+            ),
+        ])
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_bloom_filter_enabled(true)
+            .build();
+        let bytes = testing::write(&batch, props);
+        let metadata = ParquetRecordBatchReaderBuilder::try_new(bytes.clone())
+            .unwrap()
+            .metadata()
+            .clone();
+        (Input::Memory(bytes), metadata)
+    }
+
+    fn probe(s: &str) -> Probe {
+        parse_probe(s).unwrap()
+    }
+
+    fn results(probes: &[&str]) -> Vec<Option<bool>> {
+        let (input, metadata) = file();
+        let probes = probes.iter().map(|p| probe(p)).collect::<Vec<_>>();
+        check(&input, &metadata, &probes)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.result)
+            .collect()
+    }
+
+    #[test]
+    fn parses_probes() {
+        let p = probe("a.b=x=y");
+        assert_eq!((p.column.as_str(), p.value.as_str()), ("a.b", "x=y"));
+        let p = probe("name=");
+        assert_eq!((p.column.as_str(), p.value.as_str()), ("name", ""));
+        assert_eq!(parse_probe("name").unwrap_err(), "expected COLUMN=VALUE");
+    }
+
+    #[test]
+    fn finds_present_values() {
+        let present = [
+            "i32=42",
+            "u32=4294967295",
+            "i64=1099511627776",
+            "utf8=v7",
+            "dec9=3.25",
+            "dec18=99.25",
+            "dec38=0.25",
+            "date=2022-01-08",
+        ];
+        assert_eq!(results(&present), [Some(true); 8]);
+    }
+
+    #[test]
+    fn rules_out_absent_values() {
+        let absent = [
+            "i32=1000",
+            "u32=7",
+            "i64=42",
+            "utf8=v100",
+            "dec9=3.5",
+            "dec18=100.25",
+            "dec38=-0.25",
+            "date=2021-01-01",
+        ];
+        assert_eq!(results(&absent), [Some(false); 8]);
+    }
+
+    #[test]
+    fn reports_invalid_probes() {
+        let (input, metadata) = file();
+        let error = |p| match check(&input, &metadata, &[probe(p)]) {
+            Err(ParquetError::General(message)) => message,
+            _ => panic!("`{}` should fail", p),
+        };
+        assert_eq!(error("nope=1"), "no column `nope`");
+        assert!(
+            error("i32=abc").starts_with("can't parse `abc` as Int32 for column `i32`"),
+            "{}",
+            error("i32=abc")
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::Options;
+use clap::Parser;
+use parquet::errors::{ParquetError, Result};
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Settings the config file can hold, named like their options.
+const SETTINGS: &[&str] = &[
+    "null",
+    "date-format",
+    "datetime-format",
+    "timestamp-format",
+    "time-format",
+    "float-precision",
+    "duration-format",
+];
+
+/// The config file read by default: `$PQDUMP_CONFIG`, else `pqdump/config`
+/// in `$XDG_CONFIG_HOME`, else in `~/.config`.
+fn default_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("PQDUMP_CONFIG") {
+        return Some(path.into());
+    }
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("pqdump").join("config"))
+}
+
+/// Reads the settings of a config file as options. Each line holds a
+/// `name = value` setting, values can be quoted to keep surrounding spaces,
+/// and lines starting with `#` are comments.
+fn read(path: &Path) -> Result<Vec<OsString>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| ParquetError::General(format!("can't read `{}`: {}", path.display(), e)))?;
+    let mut args = vec![];
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |reason: String| {
+            ParquetError::General(format!("{}:{}: {}", path.display(), n + 1, reason))
+        };
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid("expected `name = value`".to_string()))?;
+        let name = name.trim();
+        if !SETTINGS.contains(&name) {
+            return Err(invalid(format!(
+                "unknown setting `{}`, expected one of {}",
+                name,
+                SETTINGS.join(", ")
+            )));
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        args.push(format!("--{}={}", name, value).into());
+    }
+    Ok(args)
+}
+
+/// Applies the settings of the config file, which is only required to exist
+/// when given with --config. The options are parsed again with the settings
+/// placed first, so that the command line overrides them.
+pub fn apply(options: Options) -> Result<Options> {
+    let settings = match (&options.config, default_path()) {
+        (Some(path), _) => read(path)?,
+        (None, Some(path)) if path.exists() => read(&path)?,
+        _ => vec![],
+    };
+    if settings.is_empty() {
+        return Ok(options);
+    }
+    Ok(with_settings(settings, env::args_os()))
+}
+
+/// Parses the command line `args` with `settings` placed before its options.
+fn with_settings(settings: Vec<OsString>, args: impl IntoIterator<Item = OsString>) -> Options {
+    let mut args = args.into_iter();
+    let program = args.next().unwrap_or_else(|| "pqdump".into());
+    Options::parse_from([program].into_iter().chain(settings).chain(args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn read_text(text: &str) -> Result<Vec<OsString>> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        read(file.path()).map_err(|e| match e {
+            // The path of the temporary file changes from one run to another.
+            ParquetError::General(message) => {
+                let path = file.path().display().to_string();
+                ParquetError::General(message.replace(&path, "config"))
+            }
+            e => e,
+        })
+    }
+
+    fn error(text: &str) -> String {
+        match read_text(text) {
+            Err(ParquetError::General(message)) => message,
+            _ => panic!("`{}` should fail", text),
+        }
+    }
+
+    #[test]
+    fn reads_settings() {
+        let text = "# formats\n\
+                    \n\
+                    null = NA\n\
+                    \t# indented comment\n\
+                    date-format=\"  %d/%m/%Y  \"\n\
+                    time-format = %H:%M # not a comment\n\
+                    float-precision = \"2\n";
+        assert_eq!(
+            read_text(text).unwrap(),
+            [
+                "--null=NA",
+                "--date-format=  %d/%m/%Y  ",
+                "--time-format=%H:%M # not a comment",
+                "--float-precision=\"2",
+            ]
+        );
+        assert_eq!(read_text("null = \"\"").unwrap(), ["--null="]);
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        assert_eq!(
+            error("null = NA\nnulls = NA"),
+            "config:2: unknown setting `nulls`, expected one of null, date-format, \
+             datetime-format, timestamp-format, time-format, float-precision, \
+             duration-format"
+        );
+        assert_eq!(error("null"), "config:1: expected `name = value`");
+        // Only value settings are allowed, not other options.
+        assert!(error("config = x").starts_with("config:1: unknown setting `config`"));
+        assert!(error("--null = x").starts_with("config:1: unknown setting `--null`"));
+    }
+
+    #[test]
+    fn command_line_overrides_settings() {
+        let settings = read_text("null = NA\nfloat-precision = 2").unwrap();
+        let args = ["pqdump", "--float-precision", "4", "file.parquet"];
+        let options = with_settings(settings, args.map(OsString::from));
+        assert_eq!(options.values.null, "NA");
+        assert_eq!(options.values.float_precision, Some(4));
+        assert_eq!(options.input, ["file.parquet"]);
+    }
+}
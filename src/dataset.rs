@@ -0,0 +1,433 @@
+use crate::{
+    expr::Expr,
+    input::Input,
+    partition::{self, Partitions},
+    prune::prune,
+};
+use arrow_array::ArrayRef;
+use arrow_schema::{Fields, Schema, SchemaRef};
+use parquet::{
+    arrow::arrow_reader::{
+        ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder,
+    },
+    errors::{ParquetError, Result},
+    file::{metadata::ParquetMetaData, reader::Length},
+};
+use std::{
+    collections::HashSet,
+    ffi::{OsStr, OsString},
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Name shown for standard input.
+const STDIN: &str = "-";
+
+/// Files read as one table.
+pub struct Dataset {
+    pub sources: Vec<Source>,
+    /// The columns of the files, followed by the partition columns.
+    pub schema: SchemaRef,
+    pub partitions: Fields,
+}
+
+impl Dataset {
+    /// Number of columns stored in the files.
+    pub fn num_file_columns(&self) -> usize {
+        self.schema.fields().len() - self.partitions.len()
+    }
+
+    /// The file columns among `indices`.
+    pub fn file_columns(&self, indices: &[usize]) -> Vec<usize> {
+        let n = self.num_file_columns();
+        indices.iter().copied().filter(|&i| i < n).collect()
+    }
+}
+
+/// One file of a dataset, with its footer already decoded.
+pub struct Source {
+    pub name: String,
+    /// The values of the partition columns, as single element arrays.
+    pub partition: Vec<ArrayRef>,
+    location: Location,
+    len: u64,
+    metadata: ArrowReaderMetadata,
+}
+
+/// Where the data of a source is read from. Files on disk are opened again
+/// when read, so that a dataset of many files doesn't keep them all open.
+enum Location {
+    Path(PathBuf),
+    /// Standard input and other inputs which were spooled.
+    Spooled(Input),
+}
+
+impl Source {
+    fn open(path: Option<&OsStr>, spool_size: usize, partition: Vec<ArrayRef>) -> Result<Self> {
+        let name = match path {
+            Some(path) if path != STDIN => path.to_string_lossy().into_owned(),
+            _ => STDIN.to_string(),
+        };
+        let context = |e| context(&name, e);
+        let input = Input::open(path, spool_size).map_err(context)?;
+        let metadata =
+            ArrowReaderMetadata::load(&input, ArrowReaderOptions::new()).map_err(context)?;
+        let location = match path {
+            Some(path) if path != STDIN && fs::metadata(path).is_ok_and(|m| m.is_file()) => {
+                Location::Path(path.into())
+            }
+            _ => Location::Spooled(input.clone()),
+        };
+        Ok(Self {
+            name,
+            partition,
+            location,
+            len: input.len(),
+            metadata,
+        })
+    }
+
+    pub fn metadata(&self) -> &Arc<ParquetMetaData> {
+        self.metadata.metadata()
+    }
+
+    pub fn schema(&self) -> &Schema {
+        self.metadata.schema()
+    }
+
+    /// The data of the file, opening it again when it is on disk.
+    pub fn input(&self) -> Result<Input> {
+        match &self.location {
+            Location::Path(path) => match File::open(path) {
+                Ok(file) => Ok(Input::File(Arc::new(file))),
+                Err(e) => Err(context(&self.name, e.into())),
+            },
+            Location::Spooled(input) => Ok(input.clone()),
+        }
+    }
+
+    /// The metadata of the file, with the page index when there is one.
+    pub fn load_page_index(&self) -> Result<Arc<ParquetMetaData>> {
+        let options = ArrowReaderOptions::new().with_page_index(true);
+        Ok(ArrowReaderMetadata::load(&self.input()?, options)?
+            .metadata()
+            .clone())
+    }
+
+    /// Size of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.metadata().file_metadata().num_rows() as usize
+    }
+
+    /// A reader builder of the file, reusing the decoded footer.
+    pub fn reader(&self, batch: usize) -> Result<ParquetRecordBatchReaderBuilder<Input>> {
+        let input = self.input()?;
+        Ok(
+            ParquetRecordBatchReaderBuilder::new_with_metadata(input, self.metadata.clone())
+                .with_batch_size(batch),
+        )
+    }
+}
+
+/// Prefixes an error with the name of the file it comes from.
+fn context(name: &str, e: ParquetError) -> ParquetError {
+    match e {
+        ParquetError::General(message) => ParquetError::General(format!("{}: {}", name, message)),
+        e => ParquetError::General(format!("{}: {}", name, e)),
+    }
+}
+
+/// Opens the files of the dataset, in order, and checks that they share a
+/// schema. Reads standard input when `paths` is empty.
+///
+/// Files whose partitions can't match `filter` are skipped without reading
+/// their footer.
+pub fn open(paths: &[OsString], spool_size: usize, filter: Option<&Expr>) -> Result<Dataset> {
+    if paths.is_empty() {
+        let source = Source::open(None, spool_size, vec![])?;
+        return Ok(Dataset {
+            schema: Arc::new(source.schema().clone()),
+            sources: vec![source],
+            partitions: Fields::empty(),
+        });
+    }
+    let files = expand(paths)?;
+    if files.is_empty() {
+        return Err(ParquetError::General("no input files".to_string()));
+    }
+    let partitions = Partitions::new(&files)?;
+    let keep = match filter {
+        Some(expr) => prune(expr, &partitions),
+        None => vec![true; files.len()],
+    };
+    let sources = files
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| keep[i])
+        .map(|(i, file)| {
+            Source::open(
+                Some(file.path.as_os_str()),
+                spool_size,
+                partitions.values(i),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    check_schemas(&sources)?;
+    // The schema is needed even when every file is pruned.
+    let schema = match sources.first() {
+        Some(source) => source.schema().clone(),
+        None => Source::open(Some(files[0].path.as_os_str()), spool_size, vec![])?
+            .schema()
+            .clone(),
+    };
+    for field in partitions.fields.iter() {
+        if schema.field_with_name(field.name()).is_ok() {
+            return Err(ParquetError::General(format!(
+                "partition column `{}` is also stored in the files",
+                field.name()
+            )));
+        }
+    }
+    let mut fields = schema.fields().to_vec();
+    fields.extend(partitions.fields.iter().cloned());
+    Ok(Dataset {
+        sources,
+        schema: Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone())),
+        partitions: partitions.fields,
+    })
+}
+
+/// A file of the dataset, and its path below the directory it was found in,
+/// whose `key=value` directories are its partitions.
+pub struct Found {
+    pub path: PathBuf,
+    pub relative: PathBuf,
+}
+
+impl Found {
+    fn new(path: PathBuf, root: &Path) -> Self {
+        let relative = match path.strip_prefix(root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path.file_name().map_or_else(PathBuf::new, PathBuf::from),
+        };
+        Self { path, relative }
+    }
+
+    /// A file named directly, whose partitions are the `key=value`
+    /// directories right above it, up to the first other directory.
+    fn named(path: &Path) -> Self {
+        let mut root = path.parent().unwrap_or(Path::new(""));
+        while root.file_name().is_some_and(partition::is_partition) {
+            root = root.parent().unwrap_or(Path::new(""));
+        }
+        Self::new(path.to_path_buf(), root)
+    }
+}
+
+/// Expands directories and glob patterns into the files they contain, each
+/// file only once. The partitions of a file are taken from the directories
+/// below the directory given, or below the start of the pattern without
+/// wildcards.
+pub fn expand(paths: &[OsString]) -> Result<Vec<Found>> {
+    let mut files = vec![];
+    for path in paths {
+        let path = Path::new(path);
+        if path == Path::new(STDIN) || path.exists() {
+            if path.is_dir() {
+                let mut walked = vec![];
+                walk(path, &mut walked)?;
+                if walked.is_empty() {
+                    return Err(ParquetError::General(format!(
+                        "no files found in `{}`",
+                        path.display()
+                    )));
+                }
+                files.extend(walked.into_iter().map(|file| Found::new(file, path)));
+            } else {
+                files.push(Found::named(path));
+            }
+            continue;
+        }
+        let pattern = path.to_string_lossy();
+        if !is_pattern(&pattern) {
+            // Let opening the file report the error.
+            files.push(Found::named(path));
+            continue;
+        }
+        let root = path
+            .components()
+            .take_while(|c| !is_pattern(&c.as_os_str().to_string_lossy()))
+            .collect::<PathBuf>();
+        let matches = glob::glob(&pattern)
+            .map_err(|e| ParquetError::General(format!("invalid pattern `{}`: {}", pattern, e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ParquetError::External(Box::new(e)))?;
+        if matches.is_empty() {
+            return Err(ParquetError::General(format!(
+                "no files match `{}`",
+                pattern
+            )));
+        }
+        let mut found = vec![];
+        for path in matches {
+            if path.is_dir() {
+                let mut walked = vec![];
+                walk(&path, &mut walked)?;
+                found.extend(walked.into_iter().map(|file| Found::new(file, &root)));
+            } else {
+                found.push(Found::new(path, &root));
+            }
+        }
+        // The directories matched may hold no files.
+        if found.is_empty() {
+            return Err(ParquetError::General(format!(
+                "no files match `{}`",
+                pattern
+            )));
+        }
+        files.extend(found);
+    }
+    let mut seen = HashSet::new();
+    files.retain(|file| {
+        seen.insert(fs::canonicalize(&file.path).unwrap_or_else(|_| file.path.clone()))
+    });
+    Ok(files)
+}
+
+fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// Collects the `.parquet` files under `dir` recursively, sorted by path.
+///
+/// Hidden files and those starting with `_`, such as `_SUCCESS` markers and
+/// `_delta_log` directories, are skipped.
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        let hidden = path
+            .file_name()
+            .map(|name| name.to_string_lossy().starts_with(['.', '_']))
+            .unwrap_or(true);
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            walk(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "parquet") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every file has the schema of the first one.
+fn check_schemas(sources: &[Source]) -> Result<()> {
+    let Some((first, rest)) = sources.split_first() else {
+        return Ok(());
+    };
+    for source in rest {
+        if let Some(difference) = difference(first.schema(), source.schema()) {
+            return Err(ParquetError::General(format!(
+                "schema of `{}` doesn't match `{}`: {}",
+                source.name, first.name, difference
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Describes the first difference between two schemas.
+fn difference(expected: &Schema, found: &Schema) -> Option<String> {
+    for field in expected.fields() {
+        match found.field_with_name(field.name()) {
+            Err(_) => return Some(format!("column `{}` is missing", field.name())),
+            Ok(other) if other.data_type() != field.data_type() => {
+                return Some(format!(
+                    "column `{}` is {} instead of {}",
+                    field.name(),
+                    other.data_type(),
+                    field.data_type()
+                ))
+            }
+            Ok(other) if other.is_nullable() != field.is_nullable() => {
+                return Some(format!(
+                    "column `{}` is {}nullable",
+                    field.name(),
+                    if other.is_nullable() { "" } else { "not " }
+                ))
+            }
+            Ok(_) => {}
+        }
+    }
+    if let Some(field) = found
+        .fields()
+        .iter()
+        .find(|f| expected.field_with_name(f.name()).is_err())
+    {
+        return Some(format!("unexpected column `{}`", field.name()));
+    }
+    let names = |schema: &Schema| {
+        schema
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>()
+    };
+    if names(expected) != names(found) {
+        return Some("columns are in a different order".to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(path: &str) -> PathBuf {
+        Found::named(Path::new(path)).relative
+    }
+
+    #[test]
+    fn keeps_the_partitions_of_named_files() {
+        assert_eq!(
+            relative("ds/year=2024/month=05/part-0.parquet"),
+            Path::new("year=2024/month=05/part-0.parquet")
+        );
+        assert_eq!(
+            relative("/home/a=b/ds/year=2024/part-0.parquet"),
+            Path::new("year=2024/part-0.parquet")
+        );
+        assert_eq!(
+            relative("year=2024/part-0.parquet"),
+            Path::new("year=2024/part-0.parquet")
+        );
+        assert_eq!(relative("ds/part-0.parquet"), Path::new("part-0.parquet"));
+        assert_eq!(relative("part-0.parquet"), Path::new("part-0.parquet"));
+    }
+
+    #[test]
+    fn fails_when_matched_directories_hold_no_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("empty")).unwrap();
+        fs::create_dir(dir.path().join("other")).unwrap();
+        fs::write(dir.path().join("other").join("notes.txt"), "").unwrap();
+        let pattern = dir.path().join("*").into_os_string();
+        let error = match open(std::slice::from_ref(&pattern), 0, None) {
+            Err(ParquetError::General(message)) => message,
+            _ => panic!("no dataset should be opened"),
+        };
+        assert_eq!(
+            error,
+            format!("no files match `{}`", pattern.to_string_lossy())
+        );
+    }
+}
@@ -0,0 +1,571 @@
+use arrow_arith::aggregate;
+use arrow_array::{
+    cast::AsArray,
+    types::{Float64Type, Int64Type},
+    Array, ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, UInt64Array,
+};
+use arrow_cast::{
+    cast,
+    display::{ArrayFormatter, FormatOptions},
+};
+use arrow_row::{OwnedRow, RowConverter, SortField};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use parquet::errors::Result;
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+/// Number of distinct values counted exactly. Above it, the distinct count
+/// is estimated and the most frequent values are approximate.
+const EXACT_LIMIT: usize = 100_000;
+
+/// Size in bytes of the largest value counted. Larger values are only
+/// estimated.
+const MAX_VALUE_SIZE: usize = 256;
+
+/// Bytes the exact counts of all the columns may take together. Values
+/// seen once it is spent are only estimated.
+const COUNTS_BUDGET: usize = 256 << 20;
+
+/// Number of values sampled to estimate the percentiles.
+const SAMPLE_SIZE: usize = 10_000;
+
+/// Number of most frequent values shown.
+const TOP: usize = 5;
+
+/// Schema of the summary, one row per column.
+pub fn schema() -> SchemaRef {
+    let float = |name| Field::new(name, DataType::Float64, true);
+    let count = |name, nullable| Field::new(name, DataType::UInt64, nullable);
+    Arc::new(Schema::new(vec![
+        Field::new("column", DataType::Utf8, false),
+        Field::new("type", DataType::Utf8, false),
+        count("count", false),
+        count("nulls", false),
+        count("distinct", true),
+        Field::new("distinct exact", DataType::Boolean, true),
+        Field::new("min", DataType::Utf8, true),
+        Field::new("max", DataType::Utf8, true),
+        float("mean"),
+        float("std"),
+        float("25%"),
+        float("50%"),
+        float("75%"),
+        Field::new("percentiles exact", DataType::Boolean, true),
+        count("min length", true),
+        count("max length", true),
+        Field::new("top", DataType::Utf8, true),
+    ]))
+}
+
+/// Summary statistics of the columns of a stream of record batches.
+pub struct Describe {
+    columns: Vec<Column>,
+    /// What is left of [`COUNTS_BUDGET`].
+    budget: usize,
+}
+
+impl Describe {
+    pub fn new(schema: &Schema) -> Self {
+        Self {
+            columns: schema.fields().iter().map(|f| Column::new(f)).collect(),
+            budget: COUNTS_BUDGET,
+        }
+    }
+
+    pub fn update(&mut self, batch: &RecordBatch) -> Result<()> {
+        for (column, array) in self.columns.iter_mut().zip(batch.columns()) {
+            column.update(array, &mut self.budget)?;
+        }
+        Ok(())
+    }
+
+    /// The summary, one row per column. The minimum, maximum and most
+    /// frequent values are printed with `options`.
+    pub fn finish(self, options: &FormatOptions) -> Result<RecordBatch> {
+        let summaries = self
+            .columns
+            .into_iter()
+            .map(|c| c.finish(options))
+            .collect::<Result<Vec<_>, _>>()?;
+        let strings = |f: fn(&Summary) -> Option<String>| {
+            Arc::new(summaries.iter().map(f).collect::<StringArray>()) as ArrayRef
+        };
+        let counts = |f: fn(&Summary) -> Option<u64>| {
+            Arc::new(summaries.iter().map(f).collect::<UInt64Array>()) as ArrayRef
+        };
+        let floats = |f: fn(&Summary) -> Option<f64>| {
+            Arc::new(summaries.iter().map(f).collect::<Float64Array>()) as ArrayRef
+        };
+        let percentile = |i: usize| {
+            Arc::new(
+                summaries
+                    .iter()
+                    .map(|s| s.percentiles.map(|p| p[i]))
+                    .collect::<Float64Array>(),
+            ) as ArrayRef
+        };
+        Ok(RecordBatch::try_new(
+            schema(),
+            vec![
+                strings(|s| Some(s.name.clone())),
+                strings(|s| Some(s.data_type.clone())),
+                counts(|s| Some(s.count)),
+                counts(|s| Some(s.nulls)),
+                counts(|s| s.distinct),
+                Arc::new(
+                    summaries
+                        .iter()
+                        .map(|s| s.distinct.map(|_| s.exact))
+                        .collect::<BooleanArray>(),
+                ),
+                strings(|s| s.min.clone()),
+                strings(|s| s.max.clone()),
+                floats(|s| s.mean),
+                floats(|s| s.std),
+                percentile(0),
+                percentile(1),
+                percentile(2),
+                Arc::new(
+                    summaries
+                        .iter()
+                        .map(|s| s.percentiles.map(|_| s.percentiles_exact))
+                        .collect::<BooleanArray>(),
+                ),
+                counts(|s| s.min_length),
+                counts(|s| s.max_length),
+                strings(|s| s.top.clone()),
+            ],
+        )?)
+    }
+}
+
+struct Summary {
+    name: String,
+    data_type: String,
+    count: u64,
+    nulls: u64,
+    distinct: Option<u64>,
+    exact: bool,
+    min: Option<String>,
+    max: Option<String>,
+    mean: Option<f64>,
+    std: Option<f64>,
+    percentiles: Option<[f64; 3]>,
+    percentiles_exact: bool,
+    min_length: Option<u64>,
+    max_length: Option<u64>,
+    top: Option<String>,
+}
+
+/// Accumulated statistics of one column.
+struct Column {
+    name: String,
+    data_type: DataType,
+    count: u64,
+    nulls: u64,
+    /// Present when the values can be ordered and hashed.
+    values: Option<Values>,
+    moments: Option<Moments>,
+    lengths: Option<(i64, i64)>,
+}
+
+impl Column {
+    fn new(field: &Field) -> Self {
+        let data_type = field.data_type();
+        let sort = [SortField::new(data_type.clone())];
+        let values = RowConverter::supports_fields(&sort)
+            .then(|| RowConverter::new(sort.to_vec()).ok())
+            .flatten()
+            .map(Values::new);
+        let numeric = data_type.is_numeric() && *data_type != DataType::Float16;
+        Self {
+            name: field.name().clone(),
+            data_type: data_type.clone(),
+            count: 0,
+            nulls: 0,
+            values,
+            moments: numeric.then(Moments::default),
+            lengths: None,
+        }
+    }
+
+    fn update(&mut self, array: &ArrayRef, budget: &mut usize) -> Result<(), ArrowError> {
+        self.nulls += array.null_count() as u64;
+        self.count += (array.len() - array.null_count()) as u64;
+        if let Some(values) = &mut self.values {
+            values.update(array, budget)?;
+        }
+        if let Some(moments) = &mut self.moments {
+            moments.update(cast(array, &DataType::Float64)?.as_primitive::<Float64Type>());
+        }
+        if matches!(
+            self.data_type,
+            DataType::Utf8
+                | DataType::LargeUtf8
+                | DataType::Binary
+                | DataType::LargeBinary
+                | DataType::FixedSizeBinary(_)
+        ) {
+            let lengths = cast(&arrow_string::length::length(array)?, &DataType::Int64)?;
+            let lengths = lengths.as_primitive::<Int64Type>();
+            if let (Some(min), Some(max)) = (aggregate::min(lengths), aggregate::max(lengths)) {
+                self.lengths = Some(match self.lengths {
+                    Some((lo, hi)) => (lo.min(min), hi.max(max)),
+                    None => (min, max),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, options: &FormatOptions) -> Result<Summary, ArrowError> {
+        let mut summary = Summary {
+            name: self.name,
+            data_type: self.data_type.to_string(),
+            count: self.count,
+            nulls: self.nulls,
+            distinct: None,
+            exact: false,
+            min: None,
+            max: None,
+            mean: None,
+            std: None,
+            percentiles: None,
+            percentiles_exact: false,
+            min_length: self.lengths.map(|(lo, _)| lo as u64),
+            max_length: self.lengths.map(|(_, hi)| hi as u64),
+            top: None,
+        };
+        if let Some(values) = self.values {
+            values.finish(&mut summary, options)?;
+        }
+        if let Some(moments) = self.moments.filter(|m| m.count > 0) {
+            summary.mean = Some(moments.mean);
+            summary.std =
+                (moments.count > 1).then(|| (moments.m2 / (moments.count - 1) as f64).sqrt());
+            summary.percentiles = Some([0.25, 0.5, 0.75].map(|q| moments.sample.percentile(q)));
+            summary.percentiles_exact = moments.sample.is_complete();
+        }
+        Ok(summary)
+    }
+}
+
+/// Minimum, maximum, distinct and most frequent values, compared through
+/// the row format so that any orderable type is handled the same way.
+struct Values {
+    converter: RowConverter,
+    min: Option<OwnedRow>,
+    max: Option<OwnedRow>,
+    /// Exact counts of each value while there are few enough, then the
+    /// counters of the Misra-Gries heavy hitters algorithm. Values larger
+    /// than [`MAX_VALUE_SIZE`] aren't counted, and new values are only
+    /// counted while the budget shared by the columns allows.
+    counts: HashMap<Box<[u8]>, u64>,
+    exact: bool,
+    sketch: HyperLogLog,
+}
+
+impl Values {
+    fn new(converter: RowConverter) -> Self {
+        Self {
+            converter,
+            min: None,
+            max: None,
+            counts: HashMap::new(),
+            exact: true,
+            sketch: HyperLogLog::new(),
+        }
+    }
+
+    fn update(&mut self, array: &ArrayRef, budget: &mut usize) -> Result<(), ArrowError> {
+        let rows = self
+            .converter
+            .convert_columns(std::slice::from_ref(array))?;
+        for i in (0..array.len()).filter(|&i| array.is_valid(i)) {
+            let row = rows.row(i);
+            if self.min.as_ref().is_none_or(|min| row < min.row()) {
+                self.min = Some(row.owned());
+            }
+            if self.max.as_ref().is_none_or(|max| row > max.row()) {
+                self.max = Some(row.owned());
+            }
+            let mut hasher = DefaultHasher::new();
+            row.hash(&mut hasher);
+            self.sketch.insert(hasher.finish());
+            if row.data().len() > MAX_VALUE_SIZE {
+                self.exact = false;
+            } else if let Some(count) = self.counts.get_mut(row.data()) {
+                *count += 1;
+            } else if self.counts.len() < EXACT_LIMIT && *budget >= entry_size(row.data()) {
+                *budget -= entry_size(row.data());
+                self.counts.insert(row.data().into(), 1);
+            } else {
+                self.exact = false;
+                self.counts.retain(|value, count| {
+                    *count -= 1;
+                    if *count == 0 {
+                        *budget += entry_size(value);
+                    }
+                    *count > 0
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self, summary: &mut Summary, options: &FormatOptions) -> Result<(), ArrowError> {
+        let parser = self.converter.parser();
+        let format = |row: &[u8]| -> Result<String, ArrowError> {
+            let array = self.converter.convert_rows([parser.parse(row)])?;
+            let formatter = ArrayFormatter::try_new(&array[0], options)?;
+            let value = formatter.value(0).try_to_string();
+            value
+        };
+        summary.exact = self.exact;
+        summary.distinct = Some(if self.exact {
+            self.counts.len() as u64
+        } else {
+            self.sketch.estimate()
+        });
+        summary.min = self
+            .min
+            .as_ref()
+            .map(|r| format(r.row().data()))
+            .transpose()?;
+        summary.max = self
+            .max
+            .as_ref()
+            .map(|r| format(r.row().data()))
+            .transpose()?;
+        let mut top = self.counts.iter().collect::<Vec<_>>();
+        top.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+        let top = top
+            .into_iter()
+            .take(TOP)
+            .map(|(row, count)| {
+                let approx = if self.exact { "" } else { "~" };
+                Ok(format!("{} ({}{})", format(row)?, approx, count))
+            })
+            .collect::<Result<Vec<_>, ArrowError>>()?;
+        summary.top = (!top.is_empty()).then(|| top.join(", "));
+        Ok(())
+    }
+}
+
+/// Bytes taken by the count of `value`, roughly.
+fn entry_size(value: &[u8]) -> usize {
+    value.len() + std::mem::size_of::<(Box<[u8]>, u64)>()
+}
+
+/// Running mean and variance, with a sample of the values for percentiles.
+#[derive(Default)]
+struct Moments {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    sample: Reservoir,
+}
+
+impl Moments {
+    fn update(&mut self, values: &Float64Array) {
+        let n = (values.len() - values.null_count()) as u64;
+        let Some(sum) = aggregate::sum(values).filter(|_| n > 0) else {
+            return;
+        };
+        let mean = sum / n as f64;
+        let mut m2 = 0.0;
+        for v in values.iter().flatten() {
+            m2 += (v - mean) * (v - mean);
+            self.sample.insert(v);
+        }
+        // Combines the batch with the previous ones (Chan et al.).
+        let total = self.count + n;
+        let delta = mean - self.mean;
+        self.mean += delta * n as f64 / total as f64;
+        self.m2 += m2 + delta * delta * (self.count * n) as f64 / total as f64;
+        self.count = total;
+    }
+}
+
+/// A uniform sample of at most [`SAMPLE_SIZE`] values.
+struct Reservoir {
+    values: Vec<f64>,
+    seen: u64,
+    state: u64,
+}
+
+impl Default for Reservoir {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            seen: 0,
+            state: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+}
+
+impl Reservoir {
+    fn insert(&mut self, value: f64) {
+        self.seen += 1;
+        if self.values.len() < SAMPLE_SIZE {
+            self.values.push(value);
+            return;
+        }
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        let j = self.state % self.seen;
+        if (j as usize) < SAMPLE_SIZE {
+            self.values[j as usize] = value;
+        }
+    }
+
+    /// Whether every value inserted was kept.
+    fn is_complete(&self) -> bool {
+        self.seen as usize <= SAMPLE_SIZE
+    }
+
+    /// The `q` quantile, interpolating linearly between the closest values.
+    fn percentile(&self, q: f64) -> f64 {
+        let mut sorted = self.values.clone();
+        sorted.sort_by(f64::total_cmp);
+        let pos = q * (sorted.len() - 1) as f64;
+        let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+        sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
+    }
+}
+
+/// Distinct count estimator, with 2^14 registers.
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    const BITS: u32 = 14;
+
+    fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << Self::BITS],
+        }
+    }
+
+    fn insert(&mut self, hash: u64) {
+        let index = (hash >> (64 - Self::BITS)) as usize;
+        let rank = ((hash << Self::BITS) | (1 << (Self::BITS - 1))).leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let estimate = 0.7213 / (1.0 + 1.079 / m) * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Int64Array;
+
+    fn describe(batches: &[RecordBatch]) -> RecordBatch {
+        let mut describe = Describe::new(&batches[0].schema());
+        for batch in batches {
+            describe.update(batch).unwrap();
+        }
+        describe.finish(&FormatOptions::default()).unwrap()
+    }
+
+    /// The value of `column` in the summary of the first column.
+    fn value(summary: &RecordBatch, column: &str) -> String {
+        let array = summary.column_by_name(column).unwrap();
+        let formatter = ArrayFormatter::try_new(array, &FormatOptions::default()).unwrap();
+        formatter.value(0).to_string()
+    }
+
+    #[test]
+    fn summarizes_numbers() {
+        let values = Int64Array::from(vec![Some(4), None, Some(1), Some(3), Some(1), Some(2)]);
+        let batch = RecordBatch::try_from_iter([("n", Arc::new(values) as ArrayRef)]).unwrap();
+        // Split in two batches, to combine their moments.
+        let summary = describe(&[batch.slice(0, 3), batch.slice(3, 3)]);
+        assert_eq!(value(&summary, "count"), "5");
+        assert_eq!(value(&summary, "nulls"), "1");
+        assert_eq!(value(&summary, "distinct"), "4");
+        assert_eq!(value(&summary, "distinct exact"), "true");
+        assert_eq!(value(&summary, "min"), "1");
+        assert_eq!(value(&summary, "max"), "4");
+        assert_eq!(value(&summary, "mean"), "2.2");
+        assert_eq!(value(&summary, "std"), "1.3038404810405297");
+        assert_eq!(value(&summary, "25%"), "1.0");
+        assert_eq!(value(&summary, "50%"), "2.0");
+        assert_eq!(value(&summary, "75%"), "3.0");
+        assert_eq!(value(&summary, "percentiles exact"), "true");
+        assert_eq!(value(&summary, "top"), "1 (2), 2 (1), 3 (1), 4 (1)");
+        assert_eq!(value(&summary, "min length"), "");
+    }
+
+    #[test]
+    fn summarizes_strings() {
+        let values = StringArray::from(vec![Some("b"), Some("abc"), None, Some("b")]);
+        let batch = RecordBatch::try_from_iter([("s", Arc::new(values) as ArrayRef)]).unwrap();
+        let summary = describe(&[batch]);
+        assert_eq!(value(&summary, "min"), "abc");
+        assert_eq!(value(&summary, "max"), "b");
+        assert_eq!(value(&summary, "min length"), "1");
+        assert_eq!(value(&summary, "max length"), "3");
+        assert_eq!(value(&summary, "mean"), "");
+        assert_eq!(value(&summary, "percentiles exact"), "");
+        assert_eq!(value(&summary, "top"), "b (2), abc (1)");
+    }
+
+    #[test]
+    fn estimates_above_the_limits() {
+        let n = (EXACT_LIMIT + SAMPLE_SIZE) as i64;
+        let values = Int64Array::from_iter_values((0..n).map(|i| i % (EXACT_LIMIT as i64 + 1)));
+        let batch = RecordBatch::try_from_iter([("n", Arc::new(values) as ArrayRef)]).unwrap();
+        let summary = describe(&[batch]);
+        assert_eq!(value(&summary, "distinct exact"), "false");
+        assert_eq!(value(&summary, "percentiles exact"), "false");
+        let distinct = value(&summary, "distinct").parse::<f64>().unwrap();
+        assert!(
+            (distinct / EXACT_LIMIT as f64 - 1.0).abs() < 0.05,
+            "{}",
+            distinct
+        );
+        assert!(value(&summary, "top").contains('~'));
+    }
+
+    #[test]
+    fn counts_within_the_budget() {
+        let converter = || RowConverter::new(vec![SortField::new(DataType::Utf8)]).unwrap();
+        let array = Arc::new(StringArray::from(vec!["a", "b", "a"])) as ArrayRef;
+        let rows = converter()
+            .convert_columns(std::slice::from_ref(&array))
+            .unwrap();
+        let mut budget = entry_size(rows.row(0).data()) + entry_size(rows.row(1).data());
+        let mut values = Values::new(converter());
+        values.update(&array, &mut budget).unwrap();
+        assert!(values.exact);
+        assert_eq!(values.counts.len(), 2);
+        // The budget left is shared with the next columns.
+        let mut other = Values::new(converter());
+        other.update(&array, &mut budget).unwrap();
+        assert!(!other.exact);
+        let long = Arc::new(StringArray::from(vec!["x".repeat(MAX_VALUE_SIZE)])) as ArrayRef;
+        let mut values = Values::new(converter());
+        let mut budget = usize::MAX;
+        values.update(&long, &mut budget).unwrap();
+        assert!(!values.exact);
+        assert!(values.counts.is_empty());
+    }
+}
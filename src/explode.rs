@@ -0,0 +1,196 @@
+use arrow_array::{
+    cast::AsArray, Array, ArrayRef, GenericListArray, OffsetSizeTrait, RecordBatch,
+    RecordBatchOptions, UInt32Array, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use arrow_select::take::take;
+use parquet::errors::{ParquetError, Result};
+use std::sync::Arc;
+
+/// Turns a list column into one row per element, repeating the other
+/// columns.
+pub struct Explode {
+    pub column: String,
+    /// Whether empty and null lists are kept as one row with a null element.
+    pub keep_empty: bool,
+    /// Whether to add a column with the index of each element in its list.
+    pub index: bool,
+}
+
+impl Explode {
+    /// The schema of exploded batches.
+    pub fn schema(&self, schema: &Schema) -> Result<Schema> {
+        let empty = RecordBatch::new_empty(Arc::new(schema.clone()));
+        Ok(self.apply(&empty)?.0.schema().as_ref().clone())
+    }
+
+    /// Explodes a batch, also returning the row of `batch` each row comes
+    /// from.
+    pub fn apply(&self, batch: &RecordBatch) -> Result<(RecordBatch, Vec<u32>)> {
+        let schema = batch.schema();
+        let (i, field) = schema.fields().find(&self.column).ok_or_else(|| {
+            ParquetError::General(format!("no column `{}` to explode", self.column))
+        })?;
+        let list = batch.column(i);
+        let (values, bounds) = match list.data_type() {
+            DataType::List(_) => bounds(list.as_list::<i32>()),
+            DataType::LargeList(_) => bounds(list.as_list::<i64>()),
+            DataType::FixedSizeList(_, _) => {
+                let list = list.as_fixed_size_list();
+                let bounds = (0..list.len())
+                    .map(|row| {
+                        let len = if list.is_null(row) {
+                            0
+                        } else {
+                            list.value_length()
+                        };
+                        (list.value_offset(row) as usize, len as usize)
+                    })
+                    .collect();
+                (list.values().clone(), bounds)
+            }
+            t => {
+                return Err(ParquetError::General(format!(
+                    "can't explode `{}` of type {}, which isn't a list",
+                    self.column, t
+                )))
+            }
+        };
+        let mut rows = vec![];
+        let mut elements = vec![];
+        let mut positions = vec![];
+        for (row, (start, len)) in bounds.into_iter().enumerate() {
+            if len == 0 && self.keep_empty {
+                rows.push(row as u32);
+                elements.push(None);
+                positions.push(None);
+            }
+            for k in 0..len {
+                rows.push(row as u32);
+                elements.push(Some((start + k) as u64));
+                positions.push(Some(k as u64));
+            }
+        }
+        let taken = UInt32Array::from(rows.clone());
+        let mut fields = Vec::with_capacity(batch.num_columns() + 1);
+        let mut columns = Vec::with_capacity(batch.num_columns() + 1);
+        for (j, column) in batch.columns().iter().enumerate() {
+            if j != i {
+                fields.push(schema.field(j).clone());
+                columns.push(take(column, &taken, None)?);
+                continue;
+            }
+            let element = take(&values, &UInt64Array::from(elements.clone()), None)?;
+            fields.push(Field::new(field.name(), element.data_type().clone(), true));
+            columns.push(element);
+            if self.index {
+                let name = format!("{}_index", field.name());
+                fields.push(Field::new(name, DataType::UInt64, true));
+                columns.push(Arc::new(UInt64Array::from(positions.clone())) as ArrayRef);
+            }
+        }
+        let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+        let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+        let batch = RecordBatch::try_new_with_options(Arc::new(schema), columns, &options)?;
+        Ok((batch, rows))
+    }
+}
+
+/// The values of a list, and where the elements of each row start and how
+/// many there are. Null lists have none.
+fn bounds<O: OffsetSizeTrait>(list: &GenericListArray<O>) -> (ArrayRef, Vec<(usize, usize)>) {
+    let offsets = list.value_offsets();
+    let bounds = (0..list.len())
+        .map(|row| {
+            let start = offsets[row].as_usize();
+            let len = if list.is_null(row) {
+                0
+            } else {
+                offsets[row + 1].as_usize() - start
+            };
+            (start, len)
+        })
+        .collect();
+    (list.values().clone(), bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{
+        types::{ArrowPrimitiveType, Int32Type, Int64Type, UInt64Type},
+        Int64Array, ListArray,
+    };
+
+    /// Rows 0 to 3 with the lists [1, 2], [], null and [3].
+    fn batch() -> RecordBatch {
+        let lists = ListArray::from_iter_primitive::<Int32Type, _, _>([
+            Some(vec![Some(1), Some(2)]),
+            Some(vec![]),
+            None,
+            Some(vec![Some(3)]),
+        ]);
+        RecordBatch::try_from_iter([
+            (
+                "id",
+                Arc::new(Int64Array::from(vec![0, 1, 2, 3])) as ArrayRef,
+            ),
+            ("values", Arc::new(lists) as ArrayRef),
+        ])
+        .unwrap()
+    }
+
+    fn explode(column: &str, keep_empty: bool, index: bool) -> Explode {
+        Explode {
+            column: column.to_string(),
+            keep_empty,
+            index,
+        }
+    }
+
+    fn values<T: ArrowPrimitiveType>(batch: &RecordBatch, i: usize) -> Vec<Option<T::Native>> {
+        batch.column(i).as_primitive::<T>().iter().collect()
+    }
+
+    #[test]
+    fn drops_empty_and_null_lists() {
+        let (batch, rows) = explode("values", false, false).apply(&batch()).unwrap();
+        assert_eq!(rows, [0, 0, 3]);
+        assert_eq!(values::<Int64Type>(&batch, 0), [Some(0), Some(0), Some(3)]);
+        assert_eq!(values::<Int32Type>(&batch, 1), [Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn keeps_empty_and_null_lists_as_nulls() {
+        let (batch, rows) = explode("values", true, false).apply(&batch()).unwrap();
+        assert_eq!(rows, [0, 0, 1, 2, 3]);
+        assert_eq!(
+            values::<Int32Type>(&batch, 1),
+            [Some(1), Some(2), None, None, Some(3)]
+        );
+    }
+
+    #[test]
+    fn adds_the_index_of_each_element() {
+        let explode = explode("values", true, true);
+        let input = batch();
+        let (batch, _) = explode.apply(&input).unwrap();
+        assert_eq!(batch.schema().field(2).name(), "values_index");
+        assert_eq!(
+            values::<UInt64Type>(&batch, 2),
+            [Some(0), Some(1), None, None, Some(0)]
+        );
+        let schema = explode.schema(&input.schema()).unwrap();
+        assert_eq!(&schema, batch.schema().as_ref());
+    }
+
+    #[test]
+    fn rejects_other_columns() {
+        let error = |column| {
+            let explode = explode(column, false, false);
+            explode.apply(&batch()).unwrap_err().to_string()
+        };
+        assert!(error("id").contains("can't explode `id` of type Int64"));
+        assert!(error("nope").contains("no column `nope` to explode"));
+    }
+}
@@ -0,0 +1,694 @@
+//! A small expression language for filtering rows.
+//!
+//! ```text
+//! expr      := or
+//! or        := and ("OR" and)*
+//! and       := not ("AND" not)*
+//! not       := "NOT" not | primary
+//! primary   := "(" expr ")" | operand [suffix]
+//! suffix    := op operand | "IS" ["NOT"] "NULL"
+//!            | ["NOT"] "IN" "(" operand ("," operand)* ")"
+//!            | ["NOT"] "LIKE" string
+//! operand   := column | number | string | "TRUE" | "FALSE" | "NULL"
+//! ```
+//!
+//! Columns are bare identifiers, or quoted with `"` or `` ` ``. Strings are
+//! quoted with `'`.
+
+use arrow_arith::boolean::{and_kleene, is_not_null, is_null, not, or_kleene};
+use arrow_array::{
+    cast::AsArray, new_null_array, Array, ArrayRef, BooleanArray, Datum, Float64Array, Int64Array,
+    RecordBatch, Scalar, StringArray, UInt32Array,
+};
+use arrow_cast::{cast, cast_with_options, CastOptions};
+use arrow_schema::{ArrowError, DataType};
+use std::{fmt, sync::Arc};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Column(String),
+    Literal(Literal),
+    Compare(Box<Expr>, CmpOp, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    IsNull(Box<Expr>, bool),
+    InList(Box<Expr>, Vec<Expr>, bool),
+    Like(Box<Expr>, String, bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CmpOp {
+    /// The operator with its operands swapped.
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Eq => Self::Eq,
+            Self::NotEq => Self::NotEq,
+            Self::Lt => Self::Gt,
+            Self::LtEq => Self::GtEq,
+            Self::Gt => Self::Lt,
+            Self::GtEq => Self::LtEq,
+        }
+    }
+
+    /// The operator matching exactly the rows this one doesn't.
+    pub fn negate(self) -> Self {
+        match self {
+            Self::Eq => Self::NotEq,
+            Self::NotEq => Self::Eq,
+            Self::Lt => Self::GtEq,
+            Self::LtEq => Self::Gt,
+            Self::Gt => Self::LtEq,
+            Self::GtEq => Self::Lt,
+        }
+    }
+
+    pub fn apply(self, lhs: &dyn Datum, rhs: &dyn Datum) -> Result<BooleanArray, ArrowError> {
+        use arrow_ord::cmp;
+        match self {
+            Self::Eq => cmp::eq(lhs, rhs),
+            Self::NotEq => cmp::neq(lhs, rhs),
+            Self::Lt => cmp::lt(lhs, rhs),
+            Self::LtEq => cmp::lt_eq(lhs, rhs),
+            Self::Gt => cmp::gt(lhs, rhs),
+            Self::GtEq => cmp::gt_eq(lhs, rhs),
+        }
+    }
+}
+
+impl fmt::Display for CmpOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Eq => "=",
+            Self::NotEq => "!=",
+            Self::Lt => "<",
+            Self::LtEq => "<=",
+            Self::Gt => ">",
+            Self::GtEq => ">=",
+        })
+    }
+}
+
+impl Literal {
+    /// A single element array holding the literal.
+    pub fn to_array(&self) -> ArrayRef {
+        match self {
+            Self::Null => new_null_array(&DataType::Null, 1),
+            Self::Bool(b) => Arc::new(BooleanArray::from(vec![*b])),
+            Self::Int(i) => Arc::new(Int64Array::from(vec![*i])),
+            Self::Float(f) => Arc::new(Float64Array::from(vec![*f])),
+            Self::Str(s) => Arc::new(StringArray::from(vec![s.as_str()])),
+        }
+    }
+}
+
+impl Expr {
+    /// Names of the columns referenced by the expression, without duplicates.
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns = vec![];
+        self.visit_columns(&mut |name| {
+            if !columns.contains(&name) {
+                columns.push(name);
+            }
+        });
+        columns
+    }
+
+    fn visit_columns<'a>(&'a self, f: &mut impl FnMut(&'a str)) {
+        match self {
+            Self::Column(name) => f(name),
+            Self::Literal(_) => {}
+            Self::Compare(l, _, r) | Self::And(l, r) | Self::Or(l, r) => {
+                l.visit_columns(f);
+                r.visit_columns(f);
+            }
+            Self::Not(e) | Self::IsNull(e, _) | Self::Like(e, _, _) => e.visit_columns(f),
+            Self::InList(e, list, _) => {
+                e.visit_columns(f);
+                for e in list {
+                    e.visit_columns(f);
+                }
+            }
+        }
+    }
+
+    /// Evaluates the expression as a predicate over the rows of `batch`.
+    pub fn evaluate(&self, batch: &RecordBatch) -> Result<BooleanArray, ArrowError> {
+        self.eval_bool(batch)
+    }
+
+    fn eval_bool(&self, batch: &RecordBatch) -> Result<BooleanArray, ArrowError> {
+        let len = batch.num_rows();
+        match self {
+            Self::And(l, r) => and_kleene(&l.eval_bool(batch)?, &r.eval_bool(batch)?),
+            Self::Or(l, r) => or_kleene(&l.eval_bool(batch)?, &r.eval_bool(batch)?),
+            Self::Not(e) => not(&e.eval_bool(batch)?),
+            Self::IsNull(e, negated) => {
+                let array = e.eval(batch)?.into_array(len)?;
+                if *negated {
+                    is_not_null(&array)
+                } else {
+                    is_null(&array)
+                }
+            }
+            Self::Compare(l, op, r) => {
+                let (l, r) = coerce(l.eval(batch)?, r.eval(batch)?)?;
+                Value::compare(*op, &l, &r)?.into_bool(len)
+            }
+            Self::InList(e, list, negated) => {
+                let value = e.eval(batch)?;
+                let mut result = BooleanArray::from(vec![false; len]);
+                for item in list {
+                    let (l, r) = coerce(value.clone(), item.eval(batch)?)?;
+                    let eq = Value::compare(CmpOp::Eq, &l, &r)?.into_bool(len)?;
+                    result = or_kleene(&result, &eq)?;
+                }
+                if *negated {
+                    not(&result)
+                } else {
+                    Ok(result)
+                }
+            }
+            Self::Like(e, pattern, negated) => {
+                let array = cast(&e.eval(batch)?.into_array(len)?, &DataType::Utf8)?;
+                let pattern = Scalar::new(StringArray::from(vec![pattern.as_str()]));
+                if *negated {
+                    arrow_string::like::nlike(&array, &pattern)
+                } else {
+                    arrow_string::like::like(&array, &pattern)
+                }
+            }
+            Self::Column(_) | Self::Literal(_) => self.eval(batch)?.into_bool(len),
+        }
+    }
+
+    fn eval(&self, batch: &RecordBatch) -> Result<Value, ArrowError> {
+        match self {
+            Self::Column(name) => batch
+                .column_by_name(name)
+                .cloned()
+                .map(Value::Array)
+                .ok_or_else(|| ArrowError::SchemaError(format!("unknown column `{}`", name))),
+            Self::Literal(lit) => Ok(Value::Scalar(lit.to_array())),
+            _ => Ok(Value::Array(Arc::new(self.eval_bool(batch)?))),
+        }
+    }
+}
+
+/// An evaluated operand: either a column, or a single literal value.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Array(ArrayRef),
+    Scalar(ArrayRef),
+}
+
+impl Value {
+    pub fn data_type(&self) -> &DataType {
+        match self {
+            Self::Array(a) | Self::Scalar(a) => a.data_type(),
+        }
+    }
+
+    fn cast(self, to: &DataType) -> Result<Self, ArrowError> {
+        Ok(match self {
+            Self::Array(a) => Self::Array(cast(&a, to)?),
+            // A literal that doesn't fit the column is a mistake in the
+            // expression, rather than a null.
+            Self::Scalar(a) => Self::Scalar(cast_with_options(
+                &a,
+                to,
+                &CastOptions {
+                    safe: false,
+                    ..Default::default()
+                },
+            )?),
+        })
+    }
+
+    fn into_array(self, len: usize) -> Result<ArrayRef, ArrowError> {
+        match self {
+            Self::Array(a) => Ok(a),
+            Self::Scalar(a) => arrow_select::take::take(&a, &UInt32Array::from(vec![0; len]), None),
+        }
+    }
+
+    fn into_bool(self, len: usize) -> Result<BooleanArray, ArrowError> {
+        Ok(cast(&self.into_array(len)?, &DataType::Boolean)?
+            .as_boolean()
+            .clone())
+    }
+
+    pub fn compare(op: CmpOp, lhs: &Value, rhs: &Value) -> Result<Value, ArrowError> {
+        let result = match (lhs, rhs) {
+            (Self::Array(l), Self::Array(r)) => op.apply(l, r)?,
+            (Self::Array(l), Self::Scalar(r)) => op.apply(l, &Scalar::new(r))?,
+            (Self::Scalar(l), Self::Array(r)) => op.apply(&Scalar::new(l), r)?,
+            (Self::Scalar(l), Self::Scalar(r)) => {
+                return Ok(Self::Scalar(Arc::new(op.apply(l, r)?)))
+            }
+        };
+        Ok(Self::Array(Arc::new(result)))
+    }
+}
+
+/// Casts both operands of a comparison to a common type.
+///
+/// Literals are cast to the type of the column they are compared with, except
+/// that integer columns compared with a fractional number are widened.
+pub fn coerce(lhs: Value, rhs: Value) -> Result<(Value, Value), ArrowError> {
+    let (lt, rt) = (lhs.data_type().clone(), rhs.data_type().clone());
+    if lt == rt {
+        return Ok((lhs, rhs));
+    }
+    let float = |t: &DataType| t.is_floating();
+    if (lt.is_integer() && float(&rt)) || (float(&lt) && rt.is_integer()) {
+        return Ok((lhs.cast(&DataType::Float64)?, rhs.cast(&DataType::Float64)?));
+    }
+    match (&lhs, &rhs) {
+        (Value::Scalar(_), Value::Array(_)) => Ok((lhs.cast(&rt)?, rhs)),
+        _ if rt == DataType::Null => Ok((lhs, rhs.cast(&lt)?)),
+        _ if lt == DataType::Null => Ok((lhs.cast(&rt)?, rhs)),
+        _ => Ok((lhs, rhs.cast(&lt)?)),
+    }
+}
+
+/// Parses a filter expression.
+pub fn parse(s: &str) -> Result<Expr, String> {
+    let tokens = tokenize(s)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(t) => Err(format!("unexpected {}", t)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Quoted(String),
+    Str(String),
+    Number(String),
+    Op(CmpOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(s) => write!(f, "`{}`", s),
+            Self::Quoted(s) => write!(f, "\"{}\"", s),
+            Self::Str(s) => write!(f, "'{}'", s),
+            Self::Number(s) => write!(f, "{}", s),
+            Self::Op(op) => write!(f, "`{}`", op),
+            Self::LParen => write!(f, "`(`"),
+            Self::RParen => write!(f, "`)`"),
+            Self::Comma => write!(f, "`,`"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                });
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let next = chars.peek().copied();
+                let (op, consumed) = match (c, next) {
+                    ('=', Some('=')) => (CmpOp::Eq, true),
+                    ('=', _) => (CmpOp::Eq, false),
+                    ('!', Some('=')) => (CmpOp::NotEq, true),
+                    ('<', Some('>')) => (CmpOp::NotEq, true),
+                    ('<', Some('=')) => (CmpOp::LtEq, true),
+                    ('<', _) => (CmpOp::Lt, false),
+                    ('>', Some('=')) => (CmpOp::GtEq, true),
+                    ('>', _) => (CmpOp::Gt, false),
+                    _ => return Err(format!("unexpected `{}`", c)),
+                };
+                if consumed {
+                    chars.next();
+                }
+                tokens.push(Token::Op(op));
+            }
+            '\'' | '"' | '`' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => {
+                            // A doubled quote stands for itself.
+                            if chars.peek() == Some(&c) {
+                                chars.next();
+                                text.push(c);
+                            } else {
+                                break;
+                            }
+                        }
+                        Some(ch) => text.push(ch),
+                        None => return Err(format!("unterminated {}", c)),
+                    }
+                }
+                tokens.push(if c == '\'' {
+                    Token::Str(text)
+                } else {
+                    Token::Quoted(text)
+                });
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut text = String::new();
+                text.push(c);
+                chars.next();
+                while let Some(&c) = chars.peek() {
+                    let exponent_sign = (c == '-' || c == '+') && text.ends_with(['e', 'E']);
+                    if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                        text.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Number(text));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut text = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || c == '.' {
+                        text.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(text));
+            }
+            c => return Err(format!("unexpected `{}`", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == token => Ok(()),
+            Some(t) => Err(format!("expected {}, found {}", token, t)),
+            None => Err(format!("expected {}", token)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.keyword("OR") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_not()?;
+        while self.keyword("AND") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.keyword("NOT") {
+            Ok(Expr::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(expr);
+        }
+        let operand = Box::new(self.parse_operand()?);
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            return Ok(Expr::Compare(operand, op, Box::new(self.parse_operand()?)));
+        }
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            if !self.keyword("NULL") {
+                return Err("expected NULL after IS".to_string());
+            }
+            return Ok(Expr::IsNull(operand, negated));
+        }
+        let negated = self.keyword("NOT");
+        if self.keyword("IN") {
+            self.expect(Token::LParen)?;
+            let mut list = vec![self.parse_operand()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                list.push(self.parse_operand()?);
+            }
+            self.expect(Token::RParen)?;
+            Ok(Expr::InList(operand, list, negated))
+        } else if self.keyword("LIKE") {
+            match self.next() {
+                Some(Token::Str(pattern)) => Ok(Expr::Like(operand, pattern, negated)),
+                _ => Err("expected a string after LIKE".to_string()),
+            }
+        } else if negated {
+            Err("expected IN or LIKE after NOT".to_string())
+        } else {
+            Ok(*operand)
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Ident(s)) => Ok(match s.to_ascii_uppercase().as_str() {
+                "NULL" => Expr::Literal(Literal::Null),
+                "TRUE" => Expr::Literal(Literal::Bool(true)),
+                "FALSE" => Expr::Literal(Literal::Bool(false)),
+                "AND" | "OR" | "NOT" | "IS" | "IN" | "LIKE" => {
+                    return Err(format!("unexpected {}", s.to_ascii_uppercase()))
+                }
+                _ => Expr::Column(s),
+            }),
+            Some(Token::Quoted(s)) => Ok(Expr::Column(s)),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Literal::Str(s))),
+            Some(Token::Number(s)) => {
+                if let Ok(i) = s.parse::<i64>() {
+                    Ok(Expr::Literal(Literal::Int(i)))
+                } else if let Ok(f) = s.parse::<f64>() {
+                    Ok(Expr::Literal(Literal::Float(f)))
+                } else {
+                    Err(format!("invalid number `{}`", s))
+                }
+            }
+            Some(t) => Err(format!("unexpected {}", t)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(expr: &str, batch: &RecordBatch) -> Vec<Option<bool>> {
+        parse(expr)
+            .unwrap()
+            .evaluate(batch)
+            .unwrap()
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    fn bare_columns_are_cast_to_booleans() {
+        let batch = RecordBatch::try_from_iter([(
+            "id",
+            Arc::new(Int64Array::from(vec![Some(0), Some(1), Some(2), None])) as ArrayRef,
+        )])
+        .unwrap();
+        assert_eq!(
+            evaluate("id", &batch),
+            [Some(false), Some(true), Some(true), None]
+        );
+        assert_eq!(
+            evaluate("not id", &batch),
+            [Some(true), Some(false), Some(false), None]
+        );
+    }
+
+    #[test]
+    fn nan_is_above_other_floats() {
+        let batch = RecordBatch::try_from_iter([(
+            "score",
+            Arc::new(Float64Array::from(vec![1.0, f64::NAN])) as ArrayRef,
+        )])
+        .unwrap();
+        assert_eq!(evaluate("score > 5", &batch), [Some(false), Some(true)]);
+        assert_eq!(evaluate("score = 'NaN'", &batch), [Some(false), Some(true)]);
+    }
+
+    fn column(name: &str) -> Box<Expr> {
+        Box::new(Expr::Column(name.to_string()))
+    }
+
+    fn int(i: i64) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Int(i)))
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_tighter_than_or() {
+        let a = Box::new(Expr::Compare(column("a"), CmpOp::Eq, int(1)));
+        assert_eq!(
+            parse("NOT a = 1 AND b OR c").unwrap(),
+            Expr::Or(
+                Box::new(Expr::And(Box::new(Expr::Not(a.clone())), column("b"))),
+                column("c")
+            )
+        );
+        assert_eq!(
+            parse("not (a = 1 or b) and c").unwrap(),
+            Expr::And(
+                Box::new(Expr::Not(Box::new(Expr::Or(a, column("b"))))),
+                column("c")
+            )
+        );
+    }
+
+    #[test]
+    fn parses_suffixes() {
+        assert_eq!(
+            parse("a NOT IN (1, 2)").unwrap(),
+            Expr::InList(column("a"), vec![*int(1), *int(2)], true)
+        );
+        assert_eq!(
+            parse("a in (1)").unwrap(),
+            Expr::InList(column("a"), vec![*int(1)], false)
+        );
+        assert_eq!(
+            parse("a IS NOT NULL").unwrap(),
+            Expr::IsNull(column("a"), true)
+        );
+        assert_eq!(
+            parse("a is null").unwrap(),
+            Expr::IsNull(column("a"), false)
+        );
+        assert_eq!(
+            parse("name LIKE 'a%'").unwrap(),
+            Expr::Like(column("name"), "a%".to_string(), false)
+        );
+        assert_eq!(
+            parse("name not like '_b'").unwrap(),
+            Expr::Like(column("name"), "_b".to_string(), true)
+        );
+        assert_eq!(
+            parse("a <> 1").unwrap(),
+            Expr::Compare(column("a"), CmpOp::NotEq, int(1))
+        );
+    }
+
+    #[test]
+    fn parses_literals() {
+        let literal = |s: &str| match parse(&format!("a = {}", s)).unwrap() {
+            Expr::Compare(_, _, rhs) => *rhs,
+            e => panic!("{:?}", e),
+        };
+        let float = |f| Expr::Literal(Literal::Float(f));
+        assert_eq!(literal("-5"), *int(-5));
+        assert_eq!(literal("-2.5"), float(-2.5));
+        assert_eq!(literal("1e3"), float(1e3));
+        assert_eq!(literal("1.5E-2"), float(1.5e-2));
+        assert_eq!(literal("-.5e+1"), float(-5.0));
+        assert_eq!(literal("TRUE"), Expr::Literal(Literal::Bool(true)));
+        assert_eq!(literal("null"), Expr::Literal(Literal::Null));
+        assert_eq!(
+            literal("'it''s'"),
+            Expr::Literal(Literal::Str("it's".to_string()))
+        );
+        assert_eq!(literal(r#""say ""hi""""#), *column(r#"say "hi""#));
+        assert_eq!(literal("`a``b`"), *column("a`b"));
+        assert_eq!(literal("s.x_1"), *column("s.x_1"));
+    }
+
+    #[test]
+    fn reports_errors() {
+        for (expr, message) in [
+            ("", "unexpected end of expression"),
+            ("a = ", "unexpected end of expression"),
+            ("a = 'x", "unterminated '"),
+            ("a ! 1", "unexpected `!`"),
+            ("a = 1 ;", "unexpected `;`"),
+            ("a = 1e", "invalid number `1e`"),
+            ("a = 1 b", "unexpected `b`"),
+            ("(a = 1", "expected `)`"),
+            ("a in (1 2)", "expected `)`, found 2"),
+            ("a is 1", "expected NULL after IS"),
+            ("a like b", "expected a string after LIKE"),
+            ("a not b", "expected IN or LIKE after NOT"),
+            ("a = and", "unexpected AND"),
+        ] {
+            assert_eq!(parse(expr).unwrap_err(), message, "`{}`", expr);
+        }
+    }
+}
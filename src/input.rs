@@ -0,0 +1,313 @@
+use bytes::Bytes;
+use parquet::{
+    errors::{ParquetError, Result},
+    file::{
+        metadata::ColumnChunkMetaData,
+        reader::{ChunkReader, Length},
+    },
+    format::PageHeader,
+    thrift::TSerializable,
+};
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    sync::Arc,
+};
+use thrift::protocol::TCompactInputProtocol;
+
+/// Magic bytes at the start and the end of every Parquet file.
+const MAGIC: &[u8; 4] = b"PAR1";
+
+/// A Parquet file, either on disk or spooled into memory.
+///
+/// Cloning is cheap, so the same input can be read more than once.
+#[derive(Clone)]
+pub enum Input {
+    File(Arc<File>),
+    Memory(Bytes),
+}
+
+impl Input {
+    /// Opens `path`, or standard input when it is `None` or `-`.
+    ///
+    /// Inputs that can't be seeked, such as pipes, are spooled: they are kept
+    /// in memory up to `spool_size` bytes and moved to a temporary file above
+    /// that.
+    pub fn open(path: Option<&OsStr>, spool_size: usize) -> Result<Self> {
+        let input = match path {
+            None => Self::spool(io::stdin().lock(), spool_size)?,
+            Some(path) if path == "-" => Self::spool(io::stdin().lock(), spool_size)?,
+            Some(path) => {
+                let file = File::open(path)?;
+                if file.metadata()?.is_file() {
+                    Self::File(Arc::new(file))
+                } else {
+                    Self::spool(file, spool_size)?
+                }
+            }
+        };
+        input.check()?;
+        Ok(input)
+    }
+
+    fn spool(mut reader: impl Read, spool_size: usize) -> Result<Self> {
+        let mut buffer = vec![];
+        (&mut reader)
+            .take(spool_size as u64 + 1)
+            .read_to_end(&mut buffer)?;
+        if buffer.len() <= spool_size {
+            return Ok(Self::Memory(buffer.into()));
+        }
+        let mut file = tempfile::tempfile()?;
+        file.write_all(&buffer)?;
+        io::copy(&mut reader, &mut file)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(Self::File(Arc::new(file)))
+    }
+
+    /// Checks the magic bytes, so that truncated and non-Parquet inputs are
+    /// reported as such.
+    fn check(&self) -> Result<()> {
+        let len = self.len();
+        let error = |message: &str| Err(ParquetError::General(message.to_string()));
+        if len == 0 {
+            return error("input is empty");
+        }
+        let head = self.get_bytes(0, len.min(4) as usize)?;
+        if head.as_ref() != MAGIC {
+            return error("input is not a Parquet file");
+        }
+        if len < 12 {
+            return error("input is truncated: the file is too short to hold a footer");
+        }
+        let tail = self.get_bytes(len - 4, 4)?;
+        if tail.as_ref() != MAGIC {
+            return error("input is truncated: the footer is missing");
+        }
+        Ok(())
+    }
+
+    /// Reads the Thrift structure at `offset`, such as a page header,
+    /// returning it with its length in bytes.
+    pub fn read_thrift<T: TSerializable>(&self, offset: u64, name: &str) -> Result<(T, u64)> {
+        let mut reader = Counting {
+            inner: self.get_read(offset)?,
+            count: 0,
+        };
+        let value = T::read_from_in_protocol(&mut TCompactInputProtocol::new(&mut reader))
+            .map_err(|e| {
+                ParquetError::General(format!("invalid {} at offset {}: {}", name, offset, e))
+            })?;
+        Ok((value, reader.count))
+    }
+
+    /// The headers of the pages between `start` and `end`, read one after
+    /// the other.
+    pub fn page_headers(&self, start: u64, end: u64) -> PageHeaders<'_> {
+        PageHeaders {
+            input: self,
+            offset: start,
+            end,
+        }
+    }
+}
+
+/// Offset of the first page of a column chunk: its dictionary page if it has
+/// one, else its first data page. Some writers set the dictionary page offset
+/// to 0 when there is none.
+pub fn chunk_start(chunk: &ColumnChunkMetaData) -> i64 {
+    match chunk.dictionary_page_offset() {
+        Some(offset) if offset > 0 => offset,
+        _ => chunk.data_page_offset(),
+    }
+}
+
+/// The header of a page, and where the page starts.
+pub struct PageAt {
+    pub offset: u64,
+    /// Offset of the page data, right after the header.
+    pub data: u64,
+    pub header: PageHeader,
+}
+
+impl PageAt {
+    /// Offset right after the page data.
+    pub fn end(&self) -> u64 {
+        self.data + self.header.compressed_page_size.max(0) as u64
+    }
+}
+
+/// Reads the page headers of a column chunk. The walk stops at the first
+/// header which can't be read.
+pub struct PageHeaders<'a> {
+    input: &'a Input,
+    offset: u64,
+    end: u64,
+}
+
+impl PageHeaders<'_> {
+    /// Offset of the next header.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl Iterator for PageHeaders<'_> {
+    type Item = Result<PageAt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.end {
+            return None;
+        }
+        let offset = self.offset;
+        match self.input.read_thrift::<PageHeader>(offset, "page header") {
+            Ok((header, len)) => {
+                let page = PageAt {
+                    offset,
+                    data: offset + len,
+                    header,
+                };
+                self.offset = page.end();
+                Some(Ok(page))
+            }
+            Err(e) => {
+                self.offset = self.end;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Counts the bytes read, to find where a Thrift structure ends.
+struct Counting<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+impl Length for Input {
+    fn len(&self) -> u64 {
+        match self {
+            Self::File(file) => file.len(),
+            Self::Memory(bytes) => bytes.len() as u64,
+        }
+    }
+}
+
+impl ChunkReader for Input {
+    type T = Box<dyn Read + Send>;
+
+    fn get_read(&self, start: u64) -> Result<Self::T> {
+        Ok(match self {
+            Self::File(file) => Box::new(file.get_read(start)?),
+            Self::Memory(bytes) => Box::new(bytes.get_read(start)?),
+        })
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> Result<Bytes> {
+        match self {
+            Self::File(file) => file.get_bytes(start, length),
+            Self::Memory(bytes) => bytes.get_bytes(start, length),
+        }
+    }
+}
+
+/// Parses a size in bytes, with an optional `K`, `M` or `G` suffix.
+pub fn parse_size(s: &str) -> Result<usize, String> {
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    };
+    let shift = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        _ => return Err(format!("unknown size unit `{}`", unit)),
+    };
+    let value = digits.parse::<usize>().map_err(|e| e.to_string())?;
+    value
+        .checked_mul(1 << shift)
+        .ok_or_else(|| format!("size `{}` is too large", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("512b"), Ok(512));
+        assert_eq!(parse_size("2K"), Ok(2 << 10));
+        assert_eq!(parse_size("3mb"), Ok(3 << 20));
+        assert_eq!(parse_size("1GiB"), Ok(1 << 30));
+        assert_eq!(parse_size("1T"), Err("unknown size unit `T`".to_string()));
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("99999999999999999999").is_err());
+        assert_eq!(
+            parse_size(&format!("{}G", usize::MAX >> 20)),
+            Err(format!("size `{}G` is too large", usize::MAX >> 20))
+        );
+    }
+
+    #[test]
+    fn spools_to_a_file_above_the_size() {
+        let data = b"PAR1 some data PAR1";
+        let input = Input::spool(&data[..], data.len()).unwrap();
+        assert!(matches!(input, Input::Memory(_)));
+        let input = Input::spool(&data[..], data.len() - 1).unwrap();
+        assert!(matches!(input, Input::File(_)));
+        // Both read back the same data.
+        assert_eq!(input.len(), data.len() as u64);
+        assert_eq!(input.get_bytes(0, data.len()).unwrap().as_ref(), data);
+    }
+
+    fn error(data: &'static [u8]) -> String {
+        match Input::Memory(Bytes::from_static(data)).check() {
+            Err(ParquetError::General(message)) => message,
+            _ => panic!("{:?} should be rejected", data),
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_other_files() {
+        assert_eq!(error(b""), "input is empty");
+        assert_eq!(error(b"PA"), "input is not a Parquet file");
+        assert_eq!(error(b"a,b\n1,2\n"), "input is not a Parquet file");
+        assert_eq!(
+            error(b"PAR1PAR1"),
+            "input is truncated: the file is too short to hold a footer"
+        );
+        assert_eq!(
+            error(b"PAR1 row groups"),
+            "input is truncated: the footer is missing"
+        );
+        assert!(
+            Input::Memory(Bytes::from_static(b"PAR1\0\0\0\0\0\0\0\0PAR1"))
+                .check()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn reports_corrupt_footers_as_errors() {
+        use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions};
+        let load = |data: &'static [u8]| {
+            let input = Input::Memory(Bytes::from_static(data));
+            input.check().unwrap();
+            ArrowReaderMetadata::load(&input, ArrowReaderOptions::new())
+        };
+        // A footer longer than the file, then one that isn't Thrift.
+        assert!(load(b"PAR1\xff\xff\0\0PAR1").is_err());
+        assert!(load(b"PAR1\xff\xff\xff\xff\x04\0\0\0PAR1").is_err());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::output::{optional, table};
+use arrow_schema::{DataType, Field, Schema};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use comfy_table::Cell;
+use parquet::{
+    arrow::ARROW_SCHEMA_META_KEY,
+    errors::{ParquetError, Result},
+    file::metadata::ParquetMetaData,
+    format::KeyValue,
+};
+use serde_json::{json, Value};
+
+/// The key-value metadata of the file, in the order it was written.
+fn entries(metadata: &ParquetMetaData) -> &[KeyValue] {
+    metadata
+        .file_metadata()
+        .key_value_metadata()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Decodes the Arrow schema the Arrow writer embeds, a base64 encoded IPC
+/// message.
+fn decode_arrow_schema(value: &str) -> Result<Schema> {
+    let bytes = BASE64_STANDARD
+        .decode(value)
+        .map_err(|e| ParquetError::General(format!("invalid base64: {}", e)))?;
+    // Skip the continuation marker and the length prefix.
+    let message = match bytes.get(..4) {
+        Some([255, 255, 255, 255]) => &bytes[8.min(bytes.len())..],
+        _ => &bytes,
+    };
+    let message = arrow_ipc::root_as_message(message)
+        .map_err(|e| ParquetError::General(format!("invalid IPC message: {}", e)))?;
+    let schema = message
+        .header_as_schema()
+        .ok_or_else(|| ParquetError::General("the IPC message isn't a schema".to_string()))?;
+    Ok(arrow_ipc::convert::fb_to_schema(schema))
+}
+
+/// Name of a data type, without the fields of nested types.
+fn type_name(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Struct(_) => "Struct".to_string(),
+        DataType::List(_) => "List".to_string(),
+        DataType::LargeList(_) => "LargeList".to_string(),
+        DataType::FixedSizeList(_, len) => format!("FixedSizeList({})", len),
+        DataType::Map(_, _) => "Map".to_string(),
+        t => t.to_string(),
+    }
+}
+
+/// Writes a field and its children, one per line.
+fn write_field(field: &Field, indent: usize, lines: &mut Vec<String>) {
+    lines.push(format!(
+        "{:indent$}{}: {}{}",
+        "",
+        field.name(),
+        type_name(field.data_type()),
+        if field.is_nullable() { "" } else { " not null" },
+        indent = indent
+    ));
+    match field.data_type() {
+        DataType::Struct(fields) => {
+            for child in fields {
+                write_field(child, indent + 2, lines);
+            }
+        }
+        DataType::List(child)
+        | DataType::LargeList(child)
+        | DataType::FixedSizeList(child, _)
+        | DataType::Map(child, _) => write_field(child, indent + 2, lines),
+        _ => {}
+    }
+}
+
+/// A readable rendering of a value: JSON is pretty-printed and the Arrow
+/// schema decoded.
+fn pretty(key: &str, value: &str) -> String {
+    if key == ARROW_SCHEMA_META_KEY {
+        return match decode_arrow_schema(value) {
+            Ok(schema) => {
+                let mut lines = vec![];
+                for field in schema.fields() {
+                    write_field(field, 0, &mut lines);
+                }
+                for (k, v) in schema.metadata() {
+                    lines.push(format!("{} = {}", k, v));
+                }
+                lines.join("\n")
+            }
+            Err(e) => format!("{} ({})", value, e),
+        };
+    }
+    match serde_json::from_str::<Value>(value) {
+        Ok(json @ (Value::Object(_) | Value::Array(_))) => {
+            serde_json::to_string_pretty(&json).unwrap_or_else(|_| value.to_string())
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Prints the key-value metadata of the file.
+pub fn print(metadata: &ParquetMetaData, fit_width: Option<usize>) {
+    let rows = entries(metadata).iter().map(|kv| {
+        vec![
+            Cell::new(&kv.key),
+            Cell::new(optional(kv.value.as_deref().map(|v| pretty(&kv.key, v)))),
+        ]
+    });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec!["key", "value"])
+            .add_rows(rows)
+    );
+}
+
+/// Converts the key-value metadata of the file to JSON. JSON objects and
+/// arrays are embedded as such and the Arrow schema is decoded into its fields.
+pub fn to_json(metadata: &ParquetMetaData) -> Value {
+    entries(metadata)
+        .iter()
+        .map(|kv| {
+            let value = match kv.value.as_deref() {
+                None => Value::Null,
+                Some(v) if kv.key == ARROW_SCHEMA_META_KEY => match decode_arrow_schema(v) {
+                    Ok(schema) => json!({
+                        "fields": schema
+                            .fields()
+                            .iter()
+                            .map(|f| json!({
+                                "name": f.name(),
+                                "data_type": f.data_type().to_string(),
+                                "nullable": f.is_nullable(),
+                            }))
+                            .collect::<Vec<_>>(),
+                        "metadata": schema.metadata(),
+                    }),
+                    Err(_) => Value::String(v.to_string()),
+                },
+                Some(v) => match serde_json::from_str::<Value>(v) {
+                    Ok(json @ (Value::Object(_) | Value::Array(_))) => json,
+                    _ => Value::String(v.to_string()),
+                },
+            };
+            json!({ "key": kv.key, "value": value })
+        })
+        .collect()
+}
+
+/// The raw value of `key`, empty when it has none.
+pub fn get<'a>(metadata: &'a ParquetMetaData, key: &str) -> Result<&'a str> {
+    let kv = entries(metadata)
+        .iter()
+        .find(|kv| kv.key == key)
+        .ok_or_else(|| ParquetError::General(format!("no key `{}`", key)))?;
+    Ok(kv.value.as_deref().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use arrow_array::{
+        types::Int32Type, ArrayRef, Int32Array, Int64Array, ListArray, RecordBatch, StructArray,
+    };
+    use parquet::{
+        arrow::arrow_reader::ParquetRecordBatchReaderBuilder, file::properties::WriterProperties,
+    };
+    use std::{collections::HashMap, sync::Arc};
+
+    /// A file written by the Arrow writer, which embeds its schema, with a
+    /// JSON value and a plain one.
+    fn metadata() -> Arc<ParquetMetaData> {
+        let ids = Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef;
+        let s = StructArray::from(vec![(
+            Arc::new(Field::new("a", DataType::Int32, true)),
+            Arc::new(Int32Array::from(vec![Some(1), None])) as ArrayRef,
+        )]);
+        let l = ListArray::from_iter_primitive::<Int32Type, _, _>([Some(vec![Some(1)]), None]);
+        let batch = RecordBatch::try_from_iter_with_nullable([
+            ("id", ids, false),
+            ("s", Arc::new(s) as ArrayRef, true),
+            ("l", Arc::new(l) as ArrayRef, true),
+        ])
+        .unwrap();
+        let schema = batch
+            .schema()
+            .as_ref()
+            .clone()
+            .with_metadata(HashMap::from([("origin".to_string(), "test".to_string())]));
+        let batch = batch.with_schema(Arc::new(schema)).unwrap();
+        let props = WriterProperties::builder()
+            .set_key_value_metadata(Some(vec![
+                KeyValue::new("json".to_string(), r#"{"a":[1,2]}"#.to_string()),
+                KeyValue::new("plain".to_string(), "text".to_string()),
+            ]))
+            .build();
+        ParquetRecordBatchReaderBuilder::try_new(testing::write(&batch, props))
+            .unwrap()
+            .metadata()
+            .clone()
+    }
+
+    #[test]
+    fn decodes_the_arrow_schema() {
+        let metadata = metadata();
+        let schema = get(&metadata, ARROW_SCHEMA_META_KEY).unwrap();
+        assert_eq!(
+            pretty(ARROW_SCHEMA_META_KEY, schema),
+            "id: Int64 not null\n\
+             s: Struct\n  a: Int32\n\
+             l: List\n  item: Int32\n\
+             origin = test"
+        );
+        let json = to_json(&metadata);
+        let value = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|kv| kv["key"] == ARROW_SCHEMA_META_KEY)
+            .unwrap();
+        assert_eq!(value["value"]["fields"][0]["name"], "id");
+        assert_eq!(value["value"]["fields"][0]["data_type"], "Int64");
+        assert_eq!(value["value"]["fields"][0]["nullable"], false);
+        assert_eq!(value["value"]["metadata"]["origin"], "test");
+    }
+
+    #[test]
+    fn prints_other_values() {
+        let metadata = metadata();
+        assert_eq!(
+            pretty("json", get(&metadata, "json").unwrap()),
+            "{\n  \"a\": [\n    1,\n    2\n  ]\n}"
+        );
+        assert_eq!(pretty("plain", get(&metadata, "plain").unwrap()), "text");
+        assert_eq!(pretty("number", "42"), "42");
+        let invalid = pretty(ARROW_SCHEMA_META_KEY, "not base64!");
+        assert!(invalid.starts_with("not base64! ("), "{}", invalid);
+        let json = to_json(&metadata);
+        let keys = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|kv| kv["key"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["json", "plain", ARROW_SCHEMA_META_KEY]);
+        assert_eq!(json[0]["value"], json!({ "a": [1, 2] }));
+        assert_eq!(json[1]["value"], "text");
+        assert_eq!(
+            get(&metadata, "nope").unwrap_err().to_string(),
+            "Parquet error: no key `nope`"
+        );
+    }
+}
@@ -1,15 +1,85 @@
-use arrow_array::RecordBatchReader;
-use arrow_cast::display::{ArrayFormatter, FormatOptions};
-use clap::Parser;
-use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL_CONDENSED, Cell, Table};
-use parquet::{arrow::arrow_reader::ParquetRecordBatchReaderBuilder, errors::Result};
-use std::{ffi::OsString, fs::File};
+use arrow_array::RecordBatch;
+use arrow_cast::display::DurationFormat;
+use arrow_schema::{Fields, Schema, SchemaRef};
+use clap::{Parser, ValueEnum};
+use comfy_table::Cell;
+use dataset::{Dataset, Source};
+use parquet::{
+    arrow::{
+        arrow_reader::{ArrowPredicateFn, ParquetRecordBatchReaderBuilder, RowFilter},
+        ProjectionMask,
+    },
+    errors::{ParquetError, Result},
+};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::{
+    ffi::OsString,
+    io::BufWriter,
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex},
+};
+
+mod bloom;
+
+mod config;
+
+mod dataset;
+
+mod describe;
+use describe::Describe;
+
+mod explode;
+use explode::Explode;
+
+mod expr;
+use expr::Expr;
+
+mod input;
+use input::Input;
+
+mod kv;
+
+mod metadata;
+
+mod nested;
+use nested::{Column, Path};
+
+mod output;
+use output::{table, Writer};
+
+mod pages;
+
+mod partition;
+
+mod prune;
+
+mod schema;
+
+mod sizes;
+
+mod slice;
+use slice::Matches;
+
+mod stats;
+
+#[cfg(test)]
+mod testing;
+
+mod verify;
 
 #[derive(Debug, Parser)]
-#[command(about, version, author)]
+#[command(about, version, author, args_override_self = true)]
 struct Options {
     #[arg()]
-    input: OsString,
+    /// Input files, directories or glob patterns, read as one table. Reads
+    /// standard input when missing or `-`.
+    input: Vec<OsString>,
+    #[arg(long, value_name = "SIZE", default_value = "64M", value_parser = input::parse_size)]
+    /// Size above which standard input and pipes are spooled to a temporary
+    /// file instead of memory.
+    spool_size: usize,
     #[arg(short, long, default_value = "1024")]
     /// Batch size.
     batch: usize,
@@ -19,6 +89,125 @@ struct Options {
     slice: SliceOptions,
     #[command(flatten)]
     col: ColOptions,
+    #[arg(long = "where", value_name = "EXPR", value_parser = expr::parse)]
+    /// Print only the rows matching the expression.
+    filter: Option<Expr>,
+    #[arg(long, value_enum, default_value_t = Format::Table)]
+    /// Output format.
+    format: Format,
+    #[command(flatten)]
+    values: ValueOptions,
+    #[arg(long, value_name = "FILE")]
+    /// Read formatting settings from FILE instead of `$PQDUMP_CONFIG`, else
+    /// `$XDG_CONFIG_HOME/pqdump/config`, else `~/.config/pqdump/config`.
+    /// Options on the command line take precedence.
+    config: Option<PathBuf>,
+    #[arg(long)]
+    /// Suppress the header row of CSV and TSV output.
+    no_header: bool,
+    #[arg(long)]
+    /// Add a column with the file each row was read from.
+    with_filename: bool,
+    #[arg(long, value_name = "COLUMN")]
+    /// Print one row per element of a list column, repeating the other
+    /// columns.
+    explode: Option<String>,
+    #[arg(long, requires = "explode")]
+    /// Keep the rows whose list is empty or null, with a null element.
+    explode_keep_empty: bool,
+    #[arg(long, requires = "explode")]
+    /// Add a column with the index of each element in its list, named
+    /// `COLUMN_index`.
+    explode_index: bool,
+    #[arg(long)]
+    /// Expand struct columns into one column per field, named
+    /// `parent.child`.
+    flatten: bool,
+    #[arg(long, value_name = "N")]
+    /// Only expand structs nested up to N levels deep. Implies --flatten.
+    flatten_depth: Option<usize>,
+    #[arg(short = 'x', long)]
+    /// Print each row as a block of column and value lines, headed by its
+    /// index in the file, and the file name when there are several.
+    vertical: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "no_truncate",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    /// Truncate the values of table cells to N characters, at least 1.
+    max_width: Option<usize>,
+    #[arg(long)]
+    /// Print values in full instead of fitting the table to the terminal.
+    no_truncate: bool,
+}
+
+impl Options {
+    /// Width tables are fitted to: the terminal's, unless --no-truncate is
+    /// given.
+    fn fit_width(&self) -> Option<usize> {
+        (!self.no_truncate).then(output::terminal_width).flatten()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Table,
+    Csv,
+    Tsv,
+    Json,
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DurationStyle {
+    /// Like `P198DT72932.972880S`.
+    Iso8601,
+    /// Like `198 days 16 hours 34 mins 15.407810000 secs`.
+    Pretty,
+}
+
+#[derive(Debug, Parser)]
+struct ValueOptions {
+    #[arg(long, value_name = "STRING", default_value = "")]
+    /// Print null values as STRING in tables, CSV and TSV.
+    null: String,
+    #[arg(long, value_name = "FORMAT")]
+    /// Format of dates, in the strftime syntax of chrono like `%d/%m/%Y`.
+    date_format: Option<String>,
+    #[arg(long, value_name = "FORMAT")]
+    /// Format of datetimes, which are dates with milliseconds.
+    datetime_format: Option<String>,
+    #[arg(long, value_name = "FORMAT")]
+    /// Format of timestamps.
+    timestamp_format: Option<String>,
+    #[arg(long, value_name = "FORMAT")]
+    /// Format of times of day.
+    time_format: Option<String>,
+    #[arg(long, value_name = "N")]
+    /// Print floats with N decimals in tables, CSV and TSV.
+    float_precision: Option<usize>,
+    #[arg(long, value_enum, default_value_t = DurationStyle::Iso8601)]
+    /// Format of durations.
+    duration_format: DurationStyle,
+}
+
+impl ValueOptions {
+    fn formats(&self) -> output::Formats {
+        output::Formats {
+            null: self.null.clone(),
+            date: self.date_format.clone(),
+            datetime: self.datetime_format.clone(),
+            timestamp: self.timestamp_format.clone(),
+            time: self.time_format.clone(),
+            float_precision: self.float_precision,
+            duration: match self.duration_format {
+                DurationStyle::Iso8601 => DurationFormat::ISO8601,
+                DurationStyle::Pretty => DurationFormat::Pretty,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -30,6 +219,40 @@ struct PrintOptions {
     #[arg(long)]
     /// Print the number of row groups and exit.
     num_row_groups: bool,
+    #[arg(long)]
+    /// Print the file metadata and exit.
+    metadata: bool,
+    #[arg(long)]
+    /// Print the key-value metadata and exit.
+    kv: bool,
+    #[arg(long, value_name = "NAME")]
+    /// Print the raw value of a key-value metadata key and exit.
+    kv_key: Option<String>,
+    #[arg(long)]
+    /// Print the column statistics of each row group and exit.
+    stats: bool,
+    #[arg(long)]
+    /// Print the Parquet schema and exit.
+    parquet_schema: bool,
+    #[arg(long)]
+    /// Print summary statistics of the selected rows and exit.
+    describe: bool,
+    #[arg(long)]
+    /// Print the storage size of each column and exit.
+    sizes: bool,
+    #[arg(long)]
+    /// Print the pages of each column chunk and exit.
+    pages: bool,
+    #[arg(long)]
+    /// Check the files for corruption, decoding every page, and exit.
+    verify: bool,
+    #[arg(long)]
+    /// Print the bloom filters of each column chunk and exit.
+    bloom: bool,
+    #[arg(long, value_name = "COLUMN=VALUE", value_parser = bloom::parse_probe)]
+    /// Check whether each row group might contain a value, according to its
+    /// bloom filters, and exit.
+    bloom_check: Vec<bloom::Probe>,
     #[arg(short = 'A', long)]
     /// Print the datatypes only.
     only_types: bool,
@@ -39,135 +262,878 @@ struct PrintOptions {
 }
 
 #[derive(Debug, Parser)]
-#[group(multiple = false)]
 struct SliceOptions {
-    #[arg(long)]
+    #[arg(long, conflicts_with_all = ["tail", "offset", "limit", "rows"])]
     /// Print the first rows.
     head: Option<usize>,
-    #[arg(long)]
+    #[arg(long, conflicts_with_all = ["offset", "limit", "rows"])]
     /// Print the last rows.
     tail: Option<usize>,
+    #[arg(long, conflicts_with = "rows")]
+    /// Skip the first rows.
+    offset: Option<usize>,
+    #[arg(long, conflicts_with = "rows")]
+    /// Print at most the specified number of rows.
+    limit: Option<usize>,
+    #[arg(long, value_parser = parse_rows)]
+    /// Print the rows in the range START..END.
+    rows: Option<RowRange>,
+}
+
+impl SliceOptions {
+    /// Rows to skip and to take from a file of `len` rows.
+    fn range(&self, len: usize) -> (usize, usize) {
+        if let Some(head) = self.head {
+            (0, head.min(len))
+        } else if let Some(tail) = self.tail {
+            (len.saturating_sub(tail), tail.min(len))
+        } else if let Some(rows) = &self.rows {
+            let start = rows.start.min(len);
+            let end = rows.end.unwrap_or(len).min(len);
+            (start, end.saturating_sub(start))
+        } else {
+            let skip = self.offset.unwrap_or(0).min(len);
+            let take = self.limit.unwrap_or(len).min(len - skip);
+            (skip, take)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RowRange {
+    start: usize,
+    end: Option<usize>,
+}
+
+fn parse_rows(s: &str) -> Result<RowRange, String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected START..END, found `{}`", s))?;
+    let parse = |s: &str| s.trim().parse::<usize>().map_err(|e| e.to_string());
+    let start = if start.is_empty() { 0 } else { parse(start)? };
+    let end = if end.is_empty() {
+        None
+    } else {
+        Some(parse(end)?)
+    };
+    if let Some(end) = end.filter(|&end| end < start) {
+        return Err(format!("end {} is before start {}", end, start));
+    }
+    Ok(RowRange { start, end })
 }
 
 #[derive(Debug, Parser)]
 #[group(multiple = false)]
 struct ColOptions {
     #[arg(long, value_delimiter = ',')]
-    /// Print the specified columns.
+    /// Print the specified columns. Names can be glob patterns like `meta_*`,
+    /// and nested values are selected with paths like `user.address.zip`,
+    /// `items[].sku` or `attrs{key}`.
     columns: Option<Vec<String>>,
+    #[arg(long, value_name = "REGEX")]
+    /// Print the columns whose names match a regular expression.
+    columns_regex: Option<Vec<Regex>>,
     #[arg(long, value_delimiter = ',')]
-    /// Suppress the specified columns.
+    /// Suppress the specified columns. Names can be glob patterns.
     exclude: Option<Vec<String>>,
 }
 
-fn main() -> Result<()> {
-    let args = Options::parse();
-    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(args.input)?)?
-        .with_batch_size(args.batch);
-    let metadata = reader.metadata();
+impl ColOptions {
+    /// The selected columns, in output order. Patterns select the columns
+    /// they match in schema order, and fail when they match none.
+    fn select(&self, schema: &Schema) -> Result<Vec<Column>> {
+        let fields = schema.fields();
+        let whole = |index| Column { index, path: None };
+        let mut selected = vec![];
+        let mut add = |column| {
+            if !selected.contains(&column) {
+                selected.push(column);
+            }
+        };
+        if let Some(columns) = &self.columns {
+            for name in columns {
+                if let Some((i, _)) = fields.find(name) {
+                    add(whole(i));
+                } else if let Some(pattern) = glob(name)? {
+                    let matched = matching(fields, |n| pattern.matches(n), name)?;
+                    matched.into_iter().for_each(|i| add(whole(i)));
+                } else if Path::is_path(name) {
+                    let path = Path::parse(name)?;
+                    let (i, field) = fields
+                        .find(&path.root)
+                        .ok_or_else(|| no_match(&path.root, fields))?;
+                    // Checks that the path leads somewhere.
+                    path.data_type(field.data_type())?;
+                    add(Column {
+                        index: i,
+                        path: Some(path),
+                    });
+                } else {
+                    return Err(no_match(name, fields));
+                }
+            }
+        } else if let Some(regexes) = &self.columns_regex {
+            for regex in regexes {
+                let matched = matching(fields, |n| regex.is_match(n), regex.as_str())?;
+                matched.into_iter().for_each(|i| add(whole(i)));
+            }
+        } else if let Some(exclude) = &self.exclude {
+            let mut excluded = vec![false; fields.len()];
+            for name in exclude {
+                let matched = match glob(name)? {
+                    Some(pattern) if fields.find(name).is_none() => {
+                        matching(fields, |n| pattern.matches(n), name)?
+                    }
+                    _ => matching(fields, |n| n == name, name)?,
+                };
+                matched.into_iter().for_each(|i| excluded[i] = true);
+            }
+            (0..fields.len())
+                .filter(|&i| !excluded[i])
+                .for_each(|i| add(whole(i)));
+        } else {
+            (0..fields.len()).for_each(|i| add(whole(i)));
+        }
+        Ok(selected)
+    }
+}
+
+/// Parses `name` as a glob pattern when it has wildcards. Brackets are only
+/// wildcards when they aren't the `[]` of a nested path.
+fn glob(name: &str) -> Result<Option<glob::Pattern>> {
+    let wildcards = name.contains(['*', '?'])
+        || name
+            .match_indices('[')
+            .any(|(i, _)| !name[i + 1..].starts_with(']'));
+    if !wildcards {
+        return Ok(None);
+    }
+    glob::Pattern::new(name)
+        .map(Some)
+        .map_err(|e| ParquetError::General(format!("invalid pattern `{}`: {}", name, e)))
+}
+
+/// Indices of the fields whose names match, failing when there are none.
+fn matching(fields: &Fields, matches: impl Fn(&str) -> bool, what: &str) -> Result<Vec<usize>> {
+    let matched = (0..fields.len())
+        .filter(|&i| matches(fields[i].name()))
+        .collect::<Vec<_>>();
+    if matched.is_empty() {
+        return Err(no_match(what, fields));
+    }
+    Ok(matched)
+}
+
+/// The error for a column name or pattern matching nothing, listing the
+/// names close to it.
+fn no_match(what: &str, fields: &Fields) -> ParquetError {
+    let text = what.replace(['*', '?', '^', '$'], "");
+    let mut close = fields
+        .iter()
+        .map(|f| (strsim::jaro_winkler(&text, f.name()), f.name()))
+        .filter(|(score, _)| *score >= 0.8)
+        .collect::<Vec<_>>();
+    close.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut message = format!("no column matches `{}`", what);
+    if !close.is_empty() {
+        let names = close
+            .iter()
+            .take(5)
+            .map(|(_, name)| format!("`{}`", name))
+            .collect::<Vec<_>>();
+        message += &format!("; close matches: {}", names.join(", "));
+    }
+    ParquetError::General(message)
+}
+
+/// Checks that the columns used by `expr` exist and that it can be evaluated
+/// over them, before anything is printed.
+fn check_filter(expr: &Expr, schema: &SchemaRef) -> Result<()> {
+    for name in expr.columns() {
+        if schema.field_with_name(name).is_err() {
+            return Err(ParquetError::General(format!(
+                "unknown column `{}` in --where",
+                name
+            )));
+        }
+    }
+    // Literals are cast to the types of the columns even without rows.
+    expr.evaluate(&RecordBatch::new_empty(schema.clone()))
+        .map_err(|e| ParquetError::General(format!("invalid --where: {}", e)))?;
+    Ok(())
+}
+
+/// Builds a row filter evaluating `expr` over the rows of `source`, decoding
+/// only the columns it uses. The rows let through are recorded in `matches`
+/// when given.
+fn row_filter(
+    expr: &Expr,
+    reader: &ParquetRecordBatchReaderBuilder<Input>,
+    dataset: &Dataset,
+    source: &Source,
+    matches: Option<Arc<Mutex<Matches>>>,
+) -> Result<RowFilter> {
+    let indices = expr
+        .columns()
+        .into_iter()
+        .filter_map(|name| reader.schema().index_of(name).ok())
+        .collect::<Vec<_>>();
+    let mask = ProjectionMask::roots(reader.parquet_schema(), indices);
+    let expr = expr.clone();
+    let fields = dataset.partitions.clone();
+    let values = source.partition.clone();
+    Ok(RowFilter::new(vec![Box::new(ArrowPredicateFn::new(
+        mask,
+        move |batch| {
+            let mask = expr.evaluate(&partition::append(&batch, &fields, &values)?)?;
+            if let Some(matches) = &matches {
+                matches.lock().unwrap().record(&mask);
+            }
+            Ok(mask)
+        },
+    ))]))
+}
+
+/// The row groups of `source` whose statistics don't rule out `expr`.
+fn row_groups(expr: &Expr, source: &Source) -> Vec<usize> {
+    let all = (0..source.metadata().num_row_groups()).collect();
+    prune::row_groups(expr, source.metadata(), source.schema(), all)
+}
+
+/// Counts the rows of `source` matching `expr` in the specified row groups.
+fn count_matches(
+    dataset: &Dataset,
+    source: &Source,
+    batch: usize,
+    expr: &Expr,
+    row_groups: Vec<usize>,
+) -> Result<usize> {
+    let reader = source.reader(batch)?;
+    let filter = row_filter(expr, &reader, dataset, source, None)?;
+    let mask = ProjectionMask::leaves(reader.parquet_schema(), []);
+    let reader = reader
+        .with_projection(mask)
+        .with_row_groups(row_groups)
+        .with_row_filter(filter)
+        .build()?;
+    let mut count = 0;
+    for batch in reader {
+        count += batch?.num_rows();
+    }
+    Ok(count)
+}
+
+fn main() {
+    if let Err(e) = run() {
+        match e {
+            ParquetError::General(message) => eprintln!("error: {}", message),
+            e => eprintln!("error: {}", e),
+        }
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let args = config::apply(Options::parse())?;
+    if args.print.verify {
+        return verify(&args);
+    }
+    let dataset = dataset::open(&args.input, args.spool_size, args.filter.as_ref())?;
+    if let Some(expr) = &args.filter {
+        check_filter(expr, &dataset.schema)?;
+    }
+    let sources = &dataset.sources;
     if args.print.num_row_groups {
-        println!("{}", metadata.num_row_groups());
+        let count: usize = sources.iter().map(|s| s.metadata().num_row_groups()).sum();
+        println!("{}", count);
+        return Ok(());
+    }
+    if args.print.metadata {
+        match args.format {
+            Format::Table => {
+                for source in sources {
+                    heading(sources, source);
+                    metadata::print(source.metadata(), args.fit_width());
+                }
+            }
+            Format::Json => println!(
+                "{:#}",
+                by_file(sources, "metadata", |s| Ok(metadata::to_json(s.metadata())))?
+            ),
+            format => return Err(unsupported(format, "--metadata")),
+        }
         return Ok(());
     }
-    let len = reader.metadata().file_metadata().num_rows() as usize;
-    let reader = reader.build()?;
+    if args.print.kv {
+        match args.format {
+            Format::Table => {
+                for source in sources {
+                    heading(sources, source);
+                    kv::print(source.metadata(), args.fit_width());
+                }
+            }
+            Format::Json => println!(
+                "{:#}",
+                by_file(sources, "kv", |s| Ok(kv::to_json(s.metadata())))?
+            ),
+            format => return Err(unsupported(format, "--kv")),
+        }
+        return Ok(());
+    }
+    if let Some(key) = &args.print.kv_key {
+        for source in sources {
+            heading(sources, source);
+            println!("{}", kv::get(source.metadata(), key)?);
+        }
+        return Ok(());
+    }
+    let columns = args.col.select(&dataset.schema)?;
+    // The top-level columns the selected ones are in.
+    let mut indices = vec![];
+    for column in &columns {
+        if !indices.contains(&column.index) {
+            indices.push(column.index);
+        }
+    }
+    let file_columns = dataset.file_columns(&indices);
+    // The leaf columns holding the selected ones, in the order asked for.
+    let leaves = match sources.first() {
+        Some(source) => nested::leaves(
+            source.metadata().file_metadata().schema_descr(),
+            &columns,
+            dataset.num_file_columns(),
+        )?,
+        None => vec![],
+    };
+    if args.print.stats {
+        match args.format {
+            Format::Table => {
+                for source in sources {
+                    heading(sources, source);
+                    stats::print(source.metadata(), &leaves, args.fit_width());
+                }
+            }
+            Format::Json => println!(
+                "{:#}",
+                by_file(sources, "stats", |s| Ok(stats::to_json(
+                    s.metadata(),
+                    &leaves
+                )))?
+            ),
+            format => return Err(unsupported(format, "--stats")),
+        }
+        return Ok(());
+    }
+    if args.print.sizes {
+        let files = sources
+            .iter()
+            .map(|s| (s.metadata().as_ref(), s.len()))
+            .collect::<Vec<_>>();
+        match args.format {
+            Format::Table => sizes::print(&files, &leaves, args.fit_width()),
+            Format::Json => println!("{:#}", sizes::to_json(&files, &leaves)),
+            format => return Err(unsupported(format, "--sizes")),
+        }
+        return Ok(());
+    }
+    if args.print.pages {
+        match args.format {
+            Format::Table => {
+                for source in sources {
+                    heading(sources, source);
+                    let metadata = source.load_page_index()?;
+                    pages::print(&source.input()?, &metadata, &leaves, args.fit_width())?;
+                }
+            }
+            Format::Json => println!(
+                "{:#}",
+                by_file(sources, "pages", |s| {
+                    pages::to_json(&s.input()?, &*s.load_page_index()?, &leaves)
+                })?
+            ),
+            format => return Err(unsupported(format, "--pages")),
+        }
+        return Ok(());
+    }
+    if args.print.bloom {
+        match args.format {
+            Format::Table => {
+                for source in sources {
+                    heading(sources, source);
+                    bloom::print(
+                        &source.input()?,
+                        source.metadata(),
+                        &leaves,
+                        args.fit_width(),
+                    )?;
+                }
+            }
+            Format::Json => println!(
+                "{:#}",
+                by_file(sources, "bloom_filters", |s| {
+                    bloom::to_json(&s.input()?, s.metadata(), &leaves)
+                })?
+            ),
+            format => return Err(unsupported(format, "--bloom")),
+        }
+        return Ok(());
+    }
+    if !args.print.bloom_check.is_empty() {
+        let probes = &args.print.bloom_check;
+        match args.format {
+            Format::Table => {
+                for source in sources {
+                    heading(sources, source);
+                    let input = source.input()?;
+                    bloom::print_check(&input, source.metadata(), probes, args.fit_width())?;
+                }
+            }
+            Format::Json => println!(
+                "{:#}",
+                by_file(sources, "checks", |s| {
+                    bloom::check_to_json(&s.input()?, s.metadata(), probes)
+                })?
+            ),
+            format => return Err(unsupported(format, "--bloom-check")),
+        }
+        return Ok(());
+    }
+    if args.print.parquet_schema {
+        match args.format {
+            Format::Table => {
+                for source in sources {
+                    heading(sources, source);
+                    let descr = source.metadata().file_metadata().schema_descr();
+                    schema::print(descr, &file_columns)?;
+                }
+            }
+            format => return Err(unsupported(format, "--parquet-schema")),
+        }
+        return Ok(());
+    }
+    let len: usize = sources.iter().map(|s| s.num_rows()).sum();
     if args.print.length {
+        let len = match &args.filter {
+            Some(expr) => {
+                let mut count = 0;
+                for source in sources {
+                    let row_groups = row_groups(expr, source);
+                    count += count_matches(&dataset, source, args.batch, expr, row_groups)?;
+                }
+                count
+            }
+            None => len,
+        };
         println!("{}", len);
         return Ok(());
     }
-    let schema = reader.schema();
-    if !args.print.no_types {
-        let fields = schema.fields().iter().map(|f| {
-            vec![
-                Cell::new(f.name()),
-                Cell::new(f.data_type().to_string()),
-                Cell::new(f.is_nullable().to_string()),
-            ]
-        });
-        println!(
-            "{}",
-            Table::new()
-                .load_preset(UTF8_FULL_CONDENSED)
-                .apply_modifier(UTF8_ROUND_CORNERS)
-                .set_header(vec!["name", "data type", "nullable"])
-                .add_rows(fields)
-        );
+    // The reader yields the projected columns in file order, followed by
+    // the partition columns; map them back to the order the user asked for.
+    let mut sorted = file_columns.clone();
+    sorted.sort_unstable();
+    let read = sorted
+        .iter()
+        .copied()
+        .chain(dataset.num_file_columns()..dataset.schema.fields().len())
+        .collect::<Vec<_>>();
+    let order = columns
+        .iter()
+        .map(|c| read.iter().position(|&j| j == c.index).unwrap())
+        .collect::<Vec<_>>();
+    let mut schema = nested::schema(&dataset.schema, &columns)?;
+    let explode = args.explode.as_ref().map(|column| Explode {
+        column: column.clone(),
+        keep_empty: args.explode_keep_empty,
+        index: args.explode_index,
+    });
+    if let Some(explode) = &explode {
+        schema = explode.schema(&schema)?;
+    }
+    let flatten = args.flatten || args.flatten_depth.is_some();
+    if flatten {
+        schema = nested::flatten_schema(&schema, args.flatten_depth)?;
+    }
+    if args.with_filename {
+        schema = output::with_filename_field(&schema);
+    }
+    let stdout = std::io::stdout();
+    let output_options = output::Options {
+        header: !args.no_header,
+        vertical: args.vertical,
+        max_width: args.max_width,
+        fit_width: args.fit_width(),
+        formats: args.values.formats(),
+    };
+    if args.format == Format::Table {
+        if !args.print.no_types && !args.print.describe {
+            let fields = schema.fields().iter().map(|f| {
+                vec![
+                    Cell::new(f.name()),
+                    Cell::new(f.data_type().to_string()),
+                    Cell::new(f.is_nullable().to_string()),
+                ]
+            });
+            println!(
+                "{}",
+                table(output_options.fit_width)
+                    .set_header(vec!["name", "data type", "nullable"])
+                    .add_rows(fields)
+            );
+        }
+    } else if args.print.only_types {
+        let types = output::types(&schema)?;
+        let mut writer = Writer::new(
+            BufWriter::new(stdout.lock()),
+            args.format,
+            &types.schema(),
+            &output_options,
+        )?;
+        writer.write(&types)?;
+        writer.finish()?;
+    }
+    if args.print.only_types {
+        return Ok(());
     }
-    if !args.print.only_types {
-        let field_names = schema.fields().iter().map(|f| f.name().clone());
-        let (field_indices, field_names): (Vec<_>, Vec<_>) = if let Some(columns) = args.col.columns
-        {
-            field_names
-                .enumerate()
-                .filter(|(_, n)| columns.contains(n))
-                .unzip()
-        } else if let Some(exclude) = args.col.exclude {
-            field_names
-                .enumerate()
-                .filter(|(_, n)| !exclude.contains(n))
-                .unzip()
+    let mut describe = args.print.describe.then(|| Describe::new(&schema));
+    let output_schema = match describe {
+        Some(_) => describe::schema(),
+        None => Arc::new(schema.clone()),
+    };
+    let mut writer = Writer::new(
+        BufWriter::new(stdout.lock()),
+        args.format,
+        &output_schema,
+        &output_options,
+    )?;
+    // The rows are numbered with their index in the file for --vertical,
+    // which is named when there are several.
+    let mut write = |source: &Source, batch: RecordBatch, rows: Option<Vec<usize>>| {
+        let batch = partition::append(&batch, &dataset.partitions, &source.partition)?;
+        let mut batch = nested::select(&batch, &columns, &order)?;
+        let mut rows = rows;
+        if let Some(explode) = &explode {
+            let (exploded, taken) = explode.apply(&batch)?;
+            batch = exploded;
+            rows = rows.map(|rows| taken.iter().map(|&i| rows[i as usize]).collect());
+        }
+        if flatten {
+            batch = nested::flatten(&batch, args.flatten_depth)?;
+        }
+        if let Some(describe) = &mut describe {
+            return describe.update(&batch);
+        }
+        let batch = if args.with_filename {
+            output::with_filename(&batch, &source.name)?
         } else {
-            field_names.enumerate().unzip()
+            batch
         };
-        let (skip, take) = if let Some(head) = args.slice.head {
-            (0, head.min(len))
-        } else if let Some(tail) = args.slice.tail {
-            if len <= tail {
-                (0, len)
-            } else {
-                (len - tail, tail)
+        match rows {
+            Some(rows) => {
+                let file = (sources.len() > 1).then_some(source.name.as_str());
+                writer.write_numbered(&batch, &rows, file)
             }
+            None => writer.write(&batch),
+        }
+    };
+    if let Some(expr) = &args.filter {
+        let row_groups = |source: &Source| row_groups(expr, source);
+        // The slice applies to the matching rows, so --tail needs to know
+        // how many there are.
+        let (mut skip, mut take) = if args.slice.tail.is_some() {
+            let mut count = 0;
+            for source in sources {
+                count += count_matches(&dataset, source, args.batch, expr, row_groups(source))?;
+            }
+            args.slice.range(count)
         } else {
-            (0, len)
-        };
-        let skip_batches = skip / args.batch;
-        let skip = skip % args.batch;
-        let take_batches = (skip + take) / args.batch;
-        let take_batches = if ((skip + take) % args.batch) != 0 {
-            take_batches + 1
-        } else {
-            take_batches
+            args.slice.range(usize::MAX)
         };
-        let batches = reader
+        // How many rows match in each file is only known once it is read,
+        // so the slice is applied to the batches.
+        for source in sources {
+            if take == 0 {
+                break;
+            }
+            let row_groups = row_groups(source);
+            let matches = args.vertical.then(|| {
+                let limit = skip.saturating_add(take);
+                Arc::new(Mutex::new(Matches::new(
+                    source.metadata(),
+                    &row_groups,
+                    limit,
+                )))
+            });
+            let reader = source.reader(args.batch)?;
+            let filter = row_filter(expr, &reader, &dataset, source, matches.clone())?;
+            let mask = nested::mask(
+                reader.parquet_schema(),
+                &columns,
+                dataset.num_file_columns(),
+            )?;
+            let reader = reader
+                .with_projection(mask)
+                .with_row_groups(row_groups)
+                .with_row_filter(filter)
+                .with_limit(skip.saturating_add(take))
+                .build()?;
+            for batch in reader {
+                let batch = batch?;
+                let rows = matches
+                    .as_ref()
+                    .map(|m| m.lock().unwrap().take(batch.num_rows()));
+                if skip >= batch.num_rows() {
+                    skip -= batch.num_rows();
+                    continue;
+                }
+                let len = take.min(batch.num_rows() - skip);
+                let batch = batch.slice(skip, len);
+                let rows = rows.map(|rows| rows[skip..skip + len].to_vec());
+                skip = 0;
+                take -= batch.num_rows();
+                write(source, batch, rows)?;
+                if take == 0 {
+                    break;
+                }
+            }
+        }
+    } else {
+        let (mut skip, mut take) = args.slice.range(len);
+        for source in sources {
+            if take == 0 {
+                break;
+            }
+            let rows = source.num_rows();
+            if skip >= rows {
+                skip -= rows;
+                continue;
+            }
+            let local = take.min(rows - skip);
+            let (row_groups, selection) = slice::select(source.metadata(), skip, local);
+            let mut next = skip;
+            skip = 0;
+            take -= local;
+            let reader = source.reader(args.batch)?;
+            let mask = nested::mask(
+                reader.parquet_schema(),
+                &columns,
+                dataset.num_file_columns(),
+            )?;
+            let reader = reader
+                .with_projection(mask)
+                .with_row_groups(row_groups)
+                .with_row_selection(selection)
+                .build()?;
+            for batch in reader {
+                let batch = batch?;
+                let rows = args
+                    .vertical
+                    .then(|| (next..next + batch.num_rows()).collect());
+                next += batch.num_rows();
+                write(source, batch, rows)?;
+            }
+        }
+    }
+    if let Some(describe) = describe {
+        writer.write(&describe.finish(&output_options.formats.options())?)?;
+    }
+    writer.finish()
+}
+
+/// Checks every file, even those which can't be opened, and fails when
+/// problems were found.
+fn verify(args: &Options) -> Result<()> {
+    let paths = if args.input.is_empty() {
+        vec![PathBuf::from("-")]
+    } else {
+        dataset::expand(&args.input)?
             .into_iter()
-            .skip(skip_batches)
-            .take(take_batches)
-            .collect::<Result<Vec<_>, _>>()?;
-        let columns = batches
-            .iter()
-            .map(|batch| {
-                batch
-                    .columns()
+            .map(|file| file.path)
+            .collect()
+    };
+    let reports = paths
+        .iter()
+        .map(|path| (path, verify::check(path.as_os_str(), args.spool_size)))
+        .collect::<Vec<_>>();
+    match args.format {
+        Format::Table => {
+            for (path, problems) in &reports {
+                if reports.len() > 1 {
+                    println!("==> {} <==", path.display());
+                }
+                verify::print(problems, args.fit_width());
+            }
+        }
+        Format::Json => {
+            let value = match reports.as_slice() {
+                [(_, problems)] => verify::to_json(problems),
+                reports => reports
                     .iter()
-                    .enumerate()
-                    .filter(|(i, _)| field_indices.contains(i))
-                    .map(|(_, c)| ArrayFormatter::try_new(c, &FormatOptions::default()))
-                    .collect::<Result<Vec<_>, _>>()
-                    .map(|columns| (batch.num_rows(), columns))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        let rows = columns
+                    .map(|(path, problems)| {
+                        json!({
+                            "file": path.to_string_lossy(),
+                            "problems": verify::to_json(problems),
+                        })
+                    })
+                    .collect(),
+            };
+            println!("{:#}", value);
+        }
+        format => return Err(unsupported(format, "--verify")),
+    }
+    let count: usize = reports.iter().map(|(_, problems)| problems.len()).sum();
+    if count > 0 {
+        return Err(ParquetError::General(format!(
+            "found {} problem{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        )));
+    }
+    Ok(())
+}
+
+/// Prints the name of a file before its output, when there are several.
+fn heading(sources: &[Source], source: &Source) {
+    if sources.len() > 1 {
+        println!("==> {} <==", source.name);
+    }
+}
+
+/// Converts each file to JSON, labelling the values with the file name
+/// when there are several.
+fn by_file(
+    sources: &[Source],
+    key: &str,
+    to_json: impl Fn(&Source) -> Result<Value>,
+) -> Result<Value> {
+    if let [source] = sources {
+        return to_json(source);
+    }
+    Ok(Value::Array(
+        sources
             .iter()
-            .flat_map(|(num_rows, columns)| {
-                (0..*num_rows).map(|i| {
-                    columns
-                        .iter()
-                        .map(move |col| col.value(i).try_to_string().map(Cell::new))
-                })
-            })
-            .skip(skip)
-            .take(take);
-        let mut table = Table::new();
-        for row in rows {
-            table.add_row(row.collect::<Result<Vec<_>, _>>()?);
-        }
-        println!(
-            "{}",
-            table
-                .set_header(field_names)
-                .load_preset(UTF8_FULL_CONDENSED)
-                .apply_modifier(UTF8_ROUND_CORNERS)
+            .map(|s| Ok(json!({ "file": s.name, key: to_json(s)? })))
+            .collect::<Result<_>>()?,
+    ))
+}
+
+/// Error for an output format a mode can't produce.
+fn unsupported(format: Format, mode: &str) -> ParquetError {
+    let name = format
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default();
+    ParquetError::General(format!("--format {} is not supported with {}", name, mode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_schema::{DataType, Field};
+
+    fn schema() -> Schema {
+        let field = |name| Field::new(name, DataType::Int64, true);
+        Schema::new(vec![
+            field("id"),
+            field("name"),
+            field("meta_a"),
+            field("meta_b"),
+            field("amount"),
+        ])
+    }
+
+    fn options(
+        columns: Option<&[&str]>,
+        columns_regex: Option<&[&str]>,
+        exclude: Option<&[&str]>,
+    ) -> ColOptions {
+        let strings = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        ColOptions {
+            columns: columns.map(strings),
+            columns_regex: columns_regex
+                .map(|r| r.iter().map(|r| Regex::new(r).unwrap()).collect()),
+            exclude: exclude.map(strings),
+        }
+    }
+
+    fn select(options: ColOptions) -> Result<Vec<usize>> {
+        Ok(options
+            .select(&schema())?
+            .into_iter()
+            .map(|c| c.index)
+            .collect())
+    }
+
+    fn error(options: ColOptions) -> String {
+        select(options).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn selects_all_columns_by_default() {
+        assert_eq!(select(options(None, None, None)).unwrap(), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn keeps_the_order_of_names_and_globs() {
+        let selected = select(options(Some(&["amount", "meta_*", "id"]), None, None));
+        assert_eq!(selected.unwrap(), [4, 2, 3, 0]);
+        // Columns matched by several globs are only selected once.
+        let selected = select(options(Some(&["meta_?", "*_b", "[in]*"]), None, None));
+        assert_eq!(selected.unwrap(), [2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn matches_anchored_regexes() {
+        let selected = select(options(None, Some(&["^meta_", "t$"]), None));
+        assert_eq!(selected.unwrap(), [2, 3, 4]);
+        let selected = select(options(None, Some(&["^(id|name)$"]), None));
+        assert_eq!(selected.unwrap(), [0, 1]);
+        assert_eq!(
+            error(options(None, Some(&["^eta"]), None)),
+            "Parquet error: no column matches `^eta`; close matches: `meta_a`, `meta_b`"
         );
     }
-    Ok(())
+
+    #[test]
+    fn excludes_names_and_globs() {
+        let selected = select(options(None, None, Some(&["meta_*", "id"])));
+        assert_eq!(selected.unwrap(), [1, 4]);
+    }
+
+    #[test]
+    fn suggests_close_names() {
+        assert_eq!(
+            error(options(Some(&["nmae"]), None, None)),
+            "Parquet error: no column matches `nmae`; close matches: `name`"
+        );
+        assert_eq!(
+            error(options(Some(&["meta_c*"]), None, None)),
+            "Parquet error: no column matches `meta_c*`; close matches: `meta_a`, `meta_b`"
+        );
+        assert_eq!(
+            error(options(None, None, Some(&["zzz"]))),
+            "Parquet error: no column matches `zzz`"
+        );
+    }
+
+    #[test]
+    fn checks_filters_before_reading() {
+        let schema = Arc::new(schema());
+        let check = |expr| check_filter(&expr::parse(expr).unwrap(), &schema);
+        assert!(check("id > 5 and name is not null").is_ok());
+        assert_eq!(
+            check("nope = 1").unwrap_err().to_string(),
+            "Parquet error: unknown column `nope` in --where"
+        );
+        assert_eq!(
+            check("id > 'abc'").unwrap_err().to_string(),
+            "Parquet error: invalid --where: Cast error: Cannot cast string 'abc' to value of Int64 type"
+        );
+    }
+
+    #[test]
+    fn rejects_a_max_width_of_zero() {
+        let parse = |width| Options::try_parse_from(["pqdump", "--max-width", width]);
+        assert!(parse("0").is_err());
+        assert_eq!(parse("1").unwrap().max_width, Some(1));
+    }
 }
@@ -0,0 +1,155 @@
+use crate::output::{optional, table};
+use comfy_table::Cell;
+use parquet::{
+    basic::{Compression, Encoding},
+    file::metadata::ParquetMetaData,
+};
+use serde_json::{json, Value};
+
+/// Name of a compression codec, without the level used by the writer.
+pub fn codec_name(codec: Compression) -> &'static str {
+    match codec {
+        Compression::UNCOMPRESSED => "UNCOMPRESSED",
+        Compression::SNAPPY => "SNAPPY",
+        Compression::GZIP(_) => "GZIP",
+        Compression::LZO => "LZO",
+        Compression::BROTLI(_) => "BROTLI",
+        Compression::LZ4 => "LZ4",
+        Compression::ZSTD(_) => "ZSTD",
+        Compression::LZ4_RAW => "LZ4_RAW",
+    }
+}
+
+pub fn encoding_names(encodings: &[Encoding]) -> String {
+    encodings
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Prints the file, row group and column chunk metadata as tables.
+pub fn print(metadata: &ParquetMetaData, fit_width: Option<usize>) {
+    let file = metadata.file_metadata();
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec!["key", "value"])
+            .add_rows(vec![
+                vec![Cell::new("version"), Cell::new(file.version())],
+                vec![
+                    Cell::new("created by"),
+                    Cell::new(optional(file.created_by()))
+                ],
+                vec![Cell::new("rows"), Cell::new(file.num_rows())],
+                vec![
+                    Cell::new("row groups"),
+                    Cell::new(metadata.num_row_groups())
+                ],
+                vec![
+                    Cell::new("columns"),
+                    Cell::new(file.schema_descr().num_columns()),
+                ],
+            ])
+    );
+    let row_groups = metadata.row_groups().iter().enumerate().map(|(i, rg)| {
+        vec![
+            Cell::new(i),
+            Cell::new(rg.num_rows()),
+            Cell::new(rg.total_byte_size()),
+            Cell::new(rg.compressed_size()),
+            Cell::new(optional(rg.file_offset())),
+        ]
+    });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec![
+                "row group",
+                "rows",
+                "uncompressed size",
+                "compressed size",
+                "offset",
+            ])
+            .add_rows(row_groups)
+    );
+    let columns = metadata
+        .row_groups()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, rg)| {
+            rg.columns().iter().map(move |c| {
+                vec![
+                    Cell::new(i),
+                    Cell::new(c.column_path().string()),
+                    Cell::new(c.column_type()),
+                    Cell::new(codec_name(c.compression())),
+                    Cell::new(encoding_names(c.encodings())),
+                    Cell::new(c.num_values()),
+                    Cell::new(c.compressed_size()),
+                    Cell::new(c.uncompressed_size()),
+                    Cell::new(c.data_page_offset()),
+                    Cell::new(optional(c.dictionary_page_offset())),
+                ]
+            })
+        });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec![
+                "row group",
+                "column",
+                "physical type",
+                "codec",
+                "encodings",
+                "values",
+                "compressed size",
+                "uncompressed size",
+                "data page offset",
+                "dictionary page offset",
+            ])
+            .add_rows(columns)
+    );
+}
+
+/// Converts the file, row group and column chunk metadata to JSON.
+pub fn to_json(metadata: &ParquetMetaData) -> Value {
+    let file = metadata.file_metadata();
+    let row_groups = metadata
+        .row_groups()
+        .iter()
+        .map(|rg| {
+            let columns = rg
+                .columns()
+                .iter()
+                .map(|c| {
+                    json!({
+                        "path": c.column_path().parts(),
+                        "physical_type": c.column_type().to_string(),
+                        "codec": codec_name(c.compression()),
+                        "encodings": c.encodings().iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+                        "num_values": c.num_values(),
+                        "compressed_size": c.compressed_size(),
+                        "uncompressed_size": c.uncompressed_size(),
+                        "data_page_offset": c.data_page_offset(),
+                        "dictionary_page_offset": c.dictionary_page_offset(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "num_rows": rg.num_rows(),
+                "total_byte_size": rg.total_byte_size(),
+                "compressed_size": rg.compressed_size(),
+                "file_offset": rg.file_offset(),
+                "columns": columns,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "version": file.version(),
+        "created_by": file.created_by(),
+        "num_rows": file.num_rows(),
+        "num_columns": file.schema_descr().num_columns(),
+        "row_groups": row_groups,
+    })
+}
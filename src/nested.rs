@@ -0,0 +1,600 @@
+use arrow_arith::boolean::is_null;
+use arrow_array::{
+    cast::AsArray, new_empty_array, Array, ArrayRef, GenericListArray, OffsetSizeTrait,
+    RecordBatch, RecordBatchOptions, StructArray,
+};
+use arrow_schema::{DataType, Field, FieldRef, Schema};
+use arrow_select::nullif::nullif;
+use parquet::{
+    arrow::ProjectionMask,
+    basic::{ConvertedType, LogicalType, Repetition},
+    errors::{ParquetError, Result},
+    schema::types::{SchemaDescriptor, Type},
+};
+use std::{fmt, sync::Arc};
+
+/// One step of a path into a nested column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    /// A field of a struct.
+    Field(String),
+    /// The elements of a list.
+    Elements,
+    /// The keys of a map.
+    Keys,
+    /// The values of a map.
+    Values,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Field(name) => write!(f, ".{}", name),
+            Self::Elements => write!(f, "[]"),
+            Self::Keys => write!(f, "{{key}}"),
+            Self::Values => write!(f, "{{value}}"),
+        }
+    }
+}
+
+/// A path to a value nested in a top-level column, like `user.address.zip`,
+/// `items[].sku` or `attrs{key}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path {
+    /// The path as written, which names the output column.
+    pub name: String,
+    /// Name of the top-level column.
+    pub root: String,
+    steps: Vec<Step>,
+}
+
+impl Path {
+    /// Whether `name` is written as a path rather than a column name.
+    pub fn is_path(name: &str) -> bool {
+        name.contains(['.', '[', '{'])
+    }
+
+    /// Parses a path: field names separated with dots, where `[]` selects the
+    /// elements of a list and `{key}` or `{value}` the keys or values of a map.
+    pub fn parse(name: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            ParquetError::General(format!("invalid column path `{}`: {}", name, reason))
+        };
+        let end = name.find(['.', '[', '{']).unwrap_or(name.len());
+        let (root, mut rest) = name.split_at(end);
+        if root.is_empty() {
+            return Err(invalid("expected a column name"));
+        }
+        let mut steps = vec![];
+        while !rest.is_empty() {
+            let (step, len) = if let Some(field) = rest.strip_prefix('.') {
+                let len = field.find(['.', '[', '{']).unwrap_or(field.len());
+                if len == 0 {
+                    return Err(invalid("expected a field name after `.`"));
+                }
+                (Step::Field(field[..len].to_string()), len + 1)
+            } else if rest.starts_with("[]") {
+                (Step::Elements, 2)
+            } else if rest.starts_with("{key}") {
+                (Step::Keys, 5)
+            } else if rest.starts_with("{value}") {
+                (Step::Values, 7)
+            } else {
+                return Err(invalid(&format!(
+                    "expected `.`, `[]`, `{{key}}` or `{{value}}` at `{}`",
+                    rest
+                )));
+            };
+            steps.push(step);
+            rest = &rest[len..];
+        }
+        Ok(Self {
+            name: name.to_string(),
+            root: root.to_string(),
+            steps,
+        })
+    }
+
+    /// The part of the path up to step `n`.
+    fn prefix(&self, n: usize) -> String {
+        let mut prefix = self.root.clone();
+        for step in &self.steps[..n] {
+            prefix += &step.to_string();
+        }
+        prefix
+    }
+
+    fn error(&self, n: usize, reason: &str) -> ParquetError {
+        ParquetError::General(format!(
+            "invalid column path `{}`: `{}` {}",
+            self.name,
+            self.prefix(n),
+            reason
+        ))
+    }
+
+    /// The leaf columns of the Parquet schema holding the value, which is
+    /// nested in the top-level column at `root`. Maps can't be projected to
+    /// their keys or values alone, so under a map every leaf of its entries
+    /// is read and [`Path::extract`] picks the value out.
+    pub fn leaves(&self, schema: &SchemaDescriptor, root: usize) -> Result<Vec<usize>> {
+        let mut node = schema.root_schema().get_fields()[root].as_ref();
+        let mut parts = vec![node.name()];
+        let mut in_map = false;
+        for (n, step) in self.steps.iter().enumerate() {
+            let (child, names) = match step {
+                Step::Field(name) => {
+                    if !node.is_group() || is_list(node) || is_map(node) {
+                        return Err(self.error(n, "isn't a struct"));
+                    }
+                    let child = node
+                        .get_fields()
+                        .iter()
+                        .find(|f| f.name() == name)
+                        .ok_or_else(|| self.error(n, &format!("has no field `{}`", name)))?;
+                    (child.as_ref(), vec![child.name()])
+                }
+                Step::Elements => elements(node).ok_or_else(|| self.error(n, "isn't a list"))?,
+                Step::Keys | Step::Values => {
+                    let (key_value, i) = match (is_map(node), step) {
+                        (true, Step::Keys) => (node.get_fields().first(), 0),
+                        (true, _) => (node.get_fields().first(), 1),
+                        (false, _) => (None, 0),
+                    };
+                    let child = key_value
+                        .filter(|kv| kv.is_group())
+                        .and_then(|kv| Some((kv, kv.get_fields().get(i)?)))
+                        .ok_or_else(|| self.error(n, "isn't a map"))?;
+                    if !in_map {
+                        parts.push(child.0.name());
+                        in_map = true;
+                    }
+                    (child.1.as_ref(), vec![])
+                }
+            };
+            node = child;
+            if !in_map {
+                parts.extend(names);
+            }
+        }
+        Ok((0..schema.num_columns())
+            .filter(|&j| {
+                let column = schema.column(j);
+                let path = column.path().parts();
+                path.len() >= parts.len() && path.iter().zip(&parts).all(|(a, b)| a == b)
+            })
+            .collect())
+    }
+
+    /// Extracts the value from the top-level column it is nested in. Values
+    /// under lists and maps become lists, with one element per entry.
+    pub fn extract(&self, column: &ArrayRef) -> Result<ArrayRef> {
+        self.extract_from(column, 0)
+    }
+
+    fn extract_from(&self, array: &ArrayRef, n: usize) -> Result<ArrayRef> {
+        let Some(step) = self.steps.get(n) else {
+            return Ok(array.clone());
+        };
+        match (step, array.data_type()) {
+            (Step::Field(name), DataType::Struct(_)) => {
+                let parent = array.as_struct();
+                let (i, _) = parent
+                    .fields()
+                    .find(name)
+                    .ok_or_else(|| self.error(n, &format!("has no field `{}`", name)))?;
+                self.extract_from(&child(parent, i)?, n + 1)
+            }
+            (Step::Field(_), _) => Err(self.error(n, "isn't a struct")),
+            (Step::Elements, DataType::List(field)) => {
+                let list = array.as_list::<i32>();
+                let values = self.extract_from(list.values(), n + 1)?;
+                Ok(list_like(list, field.name(), values))
+            }
+            (Step::Elements, DataType::LargeList(field)) => {
+                let list = array.as_list::<i64>();
+                let values = self.extract_from(list.values(), n + 1)?;
+                Ok(list_like(list, field.name(), values))
+            }
+            (Step::Elements, _) => Err(self.error(n, "isn't a list")),
+            (Step::Keys | Step::Values, DataType::Map(_, _)) => {
+                let map = array.as_map();
+                let entries = match step {
+                    Step::Keys => map.keys(),
+                    _ => map.values(),
+                };
+                let values = self.extract_from(entries, n + 1)?;
+                Ok(Arc::new(GenericListArray::<i32>::new(
+                    Arc::new(Field::new("item", values.data_type().clone(), true)),
+                    map.offsets().clone(),
+                    values,
+                    map.nulls().cloned(),
+                )))
+            }
+            (Step::Keys | Step::Values, _) => Err(self.error(n, "isn't a map")),
+        }
+    }
+
+    /// The type of the value, nested in a top-level column of type
+    /// `data_type`.
+    pub fn data_type(&self, data_type: &DataType) -> Result<DataType> {
+        Ok(self
+            .extract(&new_empty_array(data_type))?
+            .data_type()
+            .clone())
+    }
+
+    /// Whether the value can be null, which it can when any field along the
+    /// path can, starting with the top-level `field`.
+    pub fn is_nullable(&self, field: &Field) -> bool {
+        let mut field = field;
+        let mut nullable = field.is_nullable();
+        for step in &self.steps {
+            let next = match (step, field.data_type()) {
+                (Step::Field(name), DataType::Struct(fields)) => fields.find(name).map(|(_, f)| f),
+                (
+                    Step::Elements,
+                    DataType::List(element)
+                    | DataType::LargeList(element)
+                    | DataType::FixedSizeList(element, _),
+                ) => Some(element),
+                (Step::Keys | Step::Values, DataType::Map(entries, _)) => match entries.data_type()
+                {
+                    DataType::Struct(fields) => fields.get(usize::from(*step == Step::Values)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let Some(next) = next else {
+                return true;
+            };
+            nullable |= next.is_nullable();
+            field = next;
+        }
+        nullable
+    }
+}
+
+/// The field of a struct at `index`, null where the struct is.
+fn child(parent: &StructArray, index: usize) -> Result<ArrayRef> {
+    let child = parent.column(index);
+    Ok(match parent.nulls() {
+        Some(_) => nullif(child, &is_null(parent)?)?,
+        None => child.clone(),
+    })
+}
+
+/// A list with the same entries as `list`, holding `values` instead.
+fn list_like<O: OffsetSizeTrait>(
+    list: &GenericListArray<O>,
+    name: &str,
+    values: ArrayRef,
+) -> ArrayRef {
+    Arc::new(GenericListArray::<O>::new(
+        Arc::new(Field::new(name, values.data_type().clone(), true)),
+        list.offsets().clone(),
+        values,
+        list.nulls().cloned(),
+    ))
+}
+
+fn is_list(node: &Type) -> bool {
+    let info = node.get_basic_info();
+    node.is_group()
+        && (info.logical_type() == Some(LogicalType::List)
+            || info.converted_type() == ConvertedType::LIST)
+}
+
+fn is_map(node: &Type) -> bool {
+    let info = node.get_basic_info();
+    node.is_group()
+        && (info.logical_type() == Some(LogicalType::Map)
+            || matches!(
+                info.converted_type(),
+                ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE
+            ))
+}
+
+/// The element of a list, and the names of the nodes leading to it,
+/// following the backward compatibility rules of the Parquet format.
+fn elements(node: &Type) -> Option<(&Type, Vec<&str>)> {
+    let info = node.get_basic_info();
+    if !is_list(node) {
+        // An unannotated repeated field is a list of itself.
+        return (info.has_repetition() && info.repetition() == Repetition::REPEATED)
+            .then(|| (node, vec![]));
+    }
+    let repeated = node.get_fields().first()?.as_ref();
+    if repeated.is_group()
+        && repeated.get_fields().len() == 1
+        && repeated.name() != "array"
+        && repeated.name() != format!("{}_tuple", node.name())
+    {
+        let element = repeated.get_fields()[0].as_ref();
+        Some((element, vec![repeated.name(), element.name()]))
+    } else {
+        Some((repeated, vec![repeated.name()]))
+    }
+}
+
+/// A selected column: a top-level column, or a value nested in one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    /// Index of the top-level column.
+    pub index: usize,
+    pub path: Option<Path>,
+}
+
+/// The output field of each column, nested in `schema`.
+pub fn schema(schema: &Schema, columns: &[Column]) -> Result<Schema> {
+    let fields = columns
+        .iter()
+        .map(|c| {
+            let field = schema.field(c.index);
+            Ok(match &c.path {
+                Some(path) => Field::new(
+                    &path.name,
+                    path.data_type(field.data_type())?,
+                    path.is_nullable(field),
+                ),
+                None => field.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+/// The leaves of the Parquet schema holding the columns stored in the files,
+/// which are the first `num_file_columns`, in the order of the columns.
+pub fn leaves(
+    schema: &SchemaDescriptor,
+    columns: &[Column],
+    num_file_columns: usize,
+) -> Result<Vec<usize>> {
+    let mut leaves = vec![];
+    for column in columns.iter().filter(|c| c.index < num_file_columns) {
+        let held = match &column.path {
+            Some(path) => path.leaves(schema, column.index)?,
+            None => (0..schema.num_columns())
+                .filter(|&j| schema.get_column_root_idx(j) == column.index)
+                .collect(),
+        };
+        for j in held {
+            if !leaves.contains(&j) {
+                leaves.push(j);
+            }
+        }
+    }
+    Ok(leaves)
+}
+
+/// Projects the leaves of the Parquet schema holding the columns stored in
+/// the files, which are the first `num_file_columns`.
+pub fn mask(
+    schema: &SchemaDescriptor,
+    columns: &[Column],
+    num_file_columns: usize,
+) -> Result<ProjectionMask> {
+    Ok(ProjectionMask::leaves(
+        schema,
+        leaves(schema, columns, num_file_columns)?,
+    ))
+}
+
+/// Builds the columns from a batch, where `order` is the position of the
+/// top-level column of each.
+pub fn select(batch: &RecordBatch, columns: &[Column], order: &[usize]) -> Result<RecordBatch> {
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut arrays = Vec::with_capacity(columns.len());
+    for (column, &i) in columns.iter().zip(order) {
+        let array = batch.column(i);
+        match &column.path {
+            Some(path) => {
+                let nullable = path.is_nullable(batch.schema().field(i));
+                let array = path.extract(array)?;
+                fields.push(Field::new(&path.name, array.data_type().clone(), nullable));
+                arrays.push(array);
+            }
+            None => {
+                fields.push(batch.schema().field(i).clone());
+                arrays.push(array.clone());
+            }
+        }
+    }
+    let schema = Schema::new_with_metadata(fields, batch.schema().metadata().clone());
+    let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        Arc::new(schema),
+        arrays,
+        &options,
+    )?)
+}
+
+/// Expands the struct columns of a batch into one column per field, named
+/// `parent.child`, recursively or down to `depth` levels.
+pub fn flatten(batch: &RecordBatch, depth: Option<usize>) -> Result<RecordBatch> {
+    let mut fields = vec![];
+    let mut arrays = vec![];
+    for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+        flatten_column(field, array, depth, &mut fields, &mut arrays)?;
+    }
+    let schema = Schema::new_with_metadata(fields, batch.schema().metadata().clone());
+    let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        Arc::new(schema),
+        arrays,
+        &options,
+    )?)
+}
+
+fn flatten_column(
+    field: &FieldRef,
+    array: &ArrayRef,
+    depth: Option<usize>,
+    fields: &mut Vec<FieldRef>,
+    arrays: &mut Vec<ArrayRef>,
+) -> Result<()> {
+    match field.data_type() {
+        DataType::Struct(children) if depth != Some(0) => {
+            let parent = array.as_struct();
+            for (i, f) in children.iter().enumerate() {
+                let name = format!("{}.{}", field.name(), f.name());
+                let nullable = f.is_nullable() || field.is_nullable();
+                let f = Arc::new(f.as_ref().clone().with_name(name).with_nullable(nullable));
+                flatten_column(&f, &child(parent, i)?, depth.map(|d| d - 1), fields, arrays)?;
+            }
+        }
+        _ => {
+            fields.push(field.clone());
+            arrays.push(array.clone());
+        }
+    }
+    Ok(())
+}
+
+/// The schema of flattened batches.
+pub fn flatten_schema(schema: &Schema, depth: Option<usize>) -> Result<Schema> {
+    let empty = RecordBatch::new_empty(Arc::new(schema.clone()));
+    Ok(flatten(&empty, depth)?.schema().as_ref().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_cast::display::{ArrayFormatter, FormatOptions};
+    use arrow_schema::Fields;
+    use parquet::arrow::arrow_to_parquet_schema;
+
+    fn schema(user_nullable: bool) -> Schema {
+        let address = Field::new(
+            "address",
+            DataType::Struct(vec![Field::new("zip", DataType::Utf8, false)].into()),
+            false,
+        );
+        let user = Fields::from(vec![Field::new("name", DataType::Utf8, true), address]);
+        let item = Field::new(
+            "item",
+            DataType::Struct(vec![Field::new("sku", DataType::Utf8, true)].into()),
+            true,
+        );
+        let entries = Fields::from(vec![
+            Field::new("keys", DataType::Utf8, false),
+            Field::new("values", DataType::Int64, true),
+        ]);
+        Schema::new(vec![
+            Field::new("user", DataType::Struct(user), user_nullable),
+            Field::new("items", DataType::List(Arc::new(item)), true),
+            Field::new(
+                "attrs",
+                DataType::Map(
+                    Arc::new(Field::new("entries", DataType::Struct(entries), false)),
+                    false,
+                ),
+                true,
+            ),
+        ])
+    }
+
+    fn batch() -> RecordBatch {
+        let json = r#"
+            {"user": {"name": "a", "address": {"zip": "1"}}, "items": [{"sku": "x"}, {"sku": "y"}], "attrs": {"k": 1}}
+            {"user": null, "items": null, "attrs": {}}
+            {"user": {"name": null, "address": {"zip": "3"}}, "items": [], "attrs": {"a": 2, "b": null}}
+        "#;
+        arrow_json::ReaderBuilder::new(Arc::new(schema(true)))
+            .build(json.as_bytes())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+    }
+
+    /// The values at `path`, printed.
+    fn extract(path: &str) -> Vec<String> {
+        let batch = batch();
+        let path = Path::parse(path).unwrap();
+        let (i, _) = batch.schema().fields().find(&path.root).unwrap();
+        let array = path.extract(batch.column(i)).unwrap();
+        let formatter = ArrayFormatter::try_new(&array, &FormatOptions::default()).unwrap();
+        (0..array.len())
+            .map(|i| formatter.value(i).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parses_paths() {
+        let path = Path::parse("items[].sku").unwrap();
+        assert_eq!(path.root, "items");
+        assert_eq!(path.steps, [Step::Elements, Step::Field("sku".to_string())]);
+        let path = Path::parse("attrs{value}.a").unwrap();
+        assert_eq!(path.steps, [Step::Values, Step::Field("a".to_string())]);
+        assert!(Path::is_path("user.address"));
+        assert!(!Path::is_path("user"));
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        let error = |name| Path::parse(name).unwrap_err().to_string();
+        assert!(error(".zip").contains("expected a column name"));
+        assert!(error("user..zip").contains("expected a field name after `.`"));
+        assert!(error("attrs{keys}").contains("at `{keys}`"));
+        assert!(error("items[0]").contains("at `[0]`"));
+    }
+
+    #[test]
+    fn extracts_struct_fields() {
+        assert_eq!(extract("user.address.zip"), ["1", "", "3"]);
+        assert_eq!(extract("user.name"), ["a", "", ""]);
+    }
+
+    #[test]
+    fn extracts_list_elements_and_map_entries() {
+        assert_eq!(extract("items[].sku"), ["[x, y]", "", "[]"]);
+        assert_eq!(extract("attrs{key}"), ["[k]", "[]", "[a, b]"]);
+        assert_eq!(extract("attrs{value}"), ["[1]", "[]", "[2, ]"]);
+    }
+
+    #[test]
+    fn reports_paths_leading_nowhere() {
+        let data_type = |name: &str| {
+            let path = Path::parse(name).unwrap();
+            let schema = schema(true);
+            let field = schema.field_with_name(&path.root).unwrap();
+            path.data_type(field.data_type()).map_err(|e| e.to_string())
+        };
+        assert!(data_type("user.age")
+            .unwrap_err()
+            .contains("has no field `age`"));
+        assert!(data_type("user[]").unwrap_err().contains("isn't a list"));
+        assert!(data_type("items.sku")
+            .unwrap_err()
+            .contains("isn't a struct"));
+        assert_eq!(data_type("user.address.zip").unwrap(), DataType::Utf8);
+    }
+
+    #[test]
+    fn paths_are_nullable_when_a_field_along_them_is() {
+        let nullable = |name: &str, user_nullable| {
+            let path = Path::parse(name).unwrap();
+            let schema = schema(user_nullable);
+            path.is_nullable(schema.field_with_name(&path.root).unwrap())
+        };
+        assert!(nullable("user.address.zip", true));
+        assert!(!nullable("user.address.zip", false));
+        assert!(nullable("user.name", false));
+        assert!(nullable("attrs{key}", false));
+    }
+
+    #[test]
+    fn finds_the_leaves_of_paths() {
+        // Leaves: user.name, user.address.zip, items[].sku, attrs keys and
+        // values.
+        let parquet = arrow_to_parquet_schema(&schema(true)).unwrap();
+        let leaves = |name: &str, root| Path::parse(name).unwrap().leaves(&parquet, root).unwrap();
+        assert_eq!(leaves("user.address.zip", 0), [1]);
+        assert_eq!(leaves("user.address", 0), [1]);
+        assert_eq!(leaves("items[].sku", 1), [2]);
+        // Maps are read whole.
+        assert_eq!(leaves("attrs{value}", 2), [3, 4]);
+    }
+}
@@ -0,0 +1,914 @@
+use crate::Format;
+use arrow_array::{
+    cast::AsArray, types::Float64Type, Array, ArrayRef, BooleanArray, FixedSizeListArray,
+    LargeListArray, ListArray, MapArray, RecordBatch, RecordBatchOptions, StringArray, StructArray,
+};
+use arrow_cast::{
+    cast,
+    display::{ArrayFormatter, DurationFormat, FormatOptions},
+};
+use arrow_json::{writer::JsonArray, ArrayWriter, LineDelimitedWriter};
+use arrow_schema::{DataType, Field, FieldRef, Fields, Schema};
+use comfy_table::{
+    modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL_CONDENSED, ContentArrangement, Table,
+};
+use parquet::errors::{ParquetError, Result};
+use std::{
+    fmt,
+    io::{self, IsTerminal, Write},
+    sync::Arc,
+};
+use unicode_width::UnicodeWidthChar;
+
+/// An empty table in the style used for all output, wrapped to fit in
+/// `fit_width` when given.
+pub fn table(fit_width: Option<usize>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .apply_modifier(UTF8_ROUND_CORNERS);
+    if let Some(width) = fit_width {
+        table
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_width(width.min(u16::MAX as usize) as u16);
+    }
+    table
+}
+
+/// Width of the terminal standard output is written to, if it is one.
+pub fn terminal_width() -> Option<usize> {
+    let stdout = io::stdout();
+    if !stdout.is_terminal() {
+        return None;
+    }
+    #[cfg(unix)]
+    if let Ok(size) = rustix::termios::tcgetwinsize(&stdout) {
+        if size.ws_col > 0 {
+            return Some(size.ws_col as usize);
+        }
+    }
+    std::env::var("COLUMNS").ok()?.parse().ok()
+}
+
+/// Formats an optional value, leaving the cell empty when missing.
+pub fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// The name, data type and nullability of each field, one row per field.
+pub fn types(schema: &Schema) -> Result<RecordBatch> {
+    let fields = schema.fields();
+    Ok(RecordBatch::try_from_iter([
+        (
+            "name",
+            Arc::new(StringArray::from_iter_values(
+                fields.iter().map(|f| f.name()),
+            )) as ArrayRef,
+        ),
+        (
+            "data type",
+            Arc::new(StringArray::from_iter_values(
+                fields.iter().map(|f| f.data_type().to_string()),
+            )),
+        ),
+        (
+            "nullable",
+            Arc::new(BooleanArray::from_iter(
+                fields.iter().map(|f| Some(f.is_nullable())),
+            )),
+        ),
+    ])?)
+}
+
+/// Name of the column added by `--with-filename`.
+const FILENAME: &str = "filename";
+
+/// `schema` with a leading column for the file name.
+pub fn with_filename_field(schema: &Schema) -> Schema {
+    let mut fields = vec![Arc::new(Field::new(FILENAME, DataType::Utf8, false))];
+    fields.extend(schema.fields().iter().cloned());
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// `batch` with a leading column holding `name` in every row.
+pub fn with_filename(batch: &RecordBatch, name: &str) -> Result<RecordBatch> {
+    let schema = with_filename_field(&batch.schema());
+    let mut columns = vec![Arc::new(StringArray::from_iter_values(std::iter::repeat_n(
+        name,
+        batch.num_rows(),
+    ))) as ArrayRef];
+    columns.extend(batch.columns().iter().cloned());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// Options of the output, besides its format.
+pub struct Options {
+    /// Whether CSV and TSV start with a header row.
+    pub header: bool,
+    /// Whether tables are printed one record at a time.
+    pub vertical: bool,
+    /// Width above which the values of tables are truncated.
+    pub max_width: Option<usize>,
+    /// Width of the terminal tables are fitted to, truncating their values.
+    pub fit_width: Option<usize>,
+    pub formats: Formats,
+}
+
+/// How values are printed in tables, CSV and TSV. JSON keeps its own
+/// representation.
+#[derive(Clone, Debug)]
+pub struct Formats {
+    pub null: String,
+    pub date: Option<String>,
+    pub datetime: Option<String>,
+    /// Used for timestamps with and without a time zone.
+    pub timestamp: Option<String>,
+    pub time: Option<String>,
+    /// Number of decimals of floats, which are otherwise printed in full.
+    pub float_precision: Option<usize>,
+    pub duration: DurationFormat,
+}
+
+impl Formats {
+    /// The options of [`ArrayFormatter`] for these formats.
+    pub fn options(&self) -> FormatOptions<'_> {
+        FormatOptions::default()
+            .with_null(&self.null)
+            .with_date_format(self.date.as_deref())
+            .with_datetime_format(self.datetime.as_deref())
+            .with_timestamp_format(self.timestamp.as_deref())
+            .with_timestamp_tz_format(self.timestamp.as_deref())
+            .with_time_format(self.time.as_deref())
+            .with_duration_format(self.duration)
+    }
+
+    /// Replaces the floats of a batch, also those nested in structs, lists
+    /// and maps, with their values printed with the configured number of
+    /// decimals.
+    fn round_floats(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let Some(precision) = self.float_precision else {
+            return Ok(batch.clone());
+        };
+        let schema = batch.schema();
+        if !schema.fields().iter().any(|f| has_floats(f.data_type())) {
+            return Ok(batch.clone());
+        }
+        let mut fields = Vec::with_capacity(batch.num_columns());
+        let mut columns = Vec::with_capacity(batch.num_columns());
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            let column = round(column, precision)?;
+            fields.push(with_type(field, &column));
+            columns.push(column);
+        }
+        let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+        Ok(RecordBatch::try_new_with_options(
+            Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone())),
+            columns,
+            &options,
+        )?)
+    }
+}
+
+fn has_floats(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Struct(fields) => fields.iter().any(|f| has_floats(f.data_type())),
+        DataType::List(field)
+        | DataType::LargeList(field)
+        | DataType::FixedSizeList(field, _)
+        | DataType::Map(field, _) => has_floats(field.data_type()),
+        t => t.is_floating(),
+    }
+}
+
+/// The floats of `array` printed with `precision` decimals, walking into
+/// nested values.
+fn round(array: &ArrayRef, precision: usize) -> Result<ArrayRef> {
+    if !has_floats(array.data_type()) {
+        return Ok(array.clone());
+    }
+    Ok(match array.data_type() {
+        DataType::Struct(fields) => {
+            let array = array.as_struct();
+            let columns = array
+                .columns()
+                .iter()
+                .map(|c| round(c, precision))
+                .collect::<Result<Vec<_>>>()?;
+            let fields = fields
+                .iter()
+                .zip(&columns)
+                .map(|(f, c)| with_type(f, c))
+                .collect::<Fields>();
+            Arc::new(StructArray::try_new(
+                fields,
+                columns,
+                array.nulls().cloned(),
+            )?)
+        }
+        DataType::List(field) => {
+            let list = array.as_list::<i32>();
+            let values = round(list.values(), precision)?;
+            let field = with_type(field, &values);
+            Arc::new(ListArray::try_new(
+                field,
+                list.offsets().clone(),
+                values,
+                list.nulls().cloned(),
+            )?)
+        }
+        DataType::LargeList(field) => {
+            let list = array.as_list::<i64>();
+            let values = round(list.values(), precision)?;
+            let field = with_type(field, &values);
+            Arc::new(LargeListArray::try_new(
+                field,
+                list.offsets().clone(),
+                values,
+                list.nulls().cloned(),
+            )?)
+        }
+        DataType::FixedSizeList(field, size) => {
+            let list = array.as_fixed_size_list();
+            let values = round(list.values(), precision)?;
+            let field = with_type(field, &values);
+            Arc::new(FixedSizeListArray::try_new(
+                field,
+                *size,
+                values,
+                list.nulls().cloned(),
+            )?)
+        }
+        DataType::Map(field, sorted) => {
+            let map = array.as_map();
+            let entries = round(&(Arc::new(map.entries().clone()) as ArrayRef), precision)?;
+            let field = with_type(field, &entries);
+            Arc::new(MapArray::try_new(
+                field,
+                map.offsets().clone(),
+                entries.as_struct().clone(),
+                map.nulls().cloned(),
+                *sorted,
+            )?)
+        }
+        _ => {
+            let values = cast(array, &DataType::Float64)?;
+            Arc::new(
+                values
+                    .as_primitive::<Float64Type>()
+                    .iter()
+                    .map(|v| v.map(|v| format!("{:.*}", precision, v)))
+                    .collect::<StringArray>(),
+            )
+        }
+    })
+}
+
+/// `field` with the data type of `array`.
+fn with_type(field: &FieldRef, array: &ArrayRef) -> FieldRef {
+    Arc::new(
+        field
+            .as_ref()
+            .clone()
+            .with_data_type(array.data_type().clone()),
+    )
+}
+
+/// Writes record batches in one of the output formats.
+pub enum Writer<W: Write> {
+    Table(TableWriter<W>),
+    Vertical(VerticalWriter<W>),
+    Csv(Box<arrow_csv::Writer<W>>, Formats),
+    /// The JSON writer is only created for the first row, as its versions
+    /// differ on what they print for an empty array.
+    Json {
+        out: Option<W>,
+        writer: Option<ArrayWriter<W>>,
+    },
+    Ndjson(LineDelimitedWriter<W>),
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a writer for `format`. The header is only optional for CSV
+    /// and TSV, and only tables can be vertical.
+    pub fn new(out: W, format: Format, schema: &Schema, options: &Options) -> Result<Self> {
+        if options.vertical && format != Format::Table {
+            return Err(ParquetError::General(
+                "--vertical only applies to table output".to_string(),
+            ));
+        }
+        if matches!(format, Format::Csv | Format::Tsv) {
+            if let Some(f) = schema.fields().iter().find(|f| f.data_type().is_nested()) {
+                return Err(ParquetError::General(format!(
+                    "nested column `{}` can't be written as CSV",
+                    f.name()
+                )));
+            }
+        }
+        let formats = &options.formats;
+        let mut csv = arrow_csv::WriterBuilder::new()
+            .with_header(options.header)
+            .with_null(formats.null.clone());
+        if let Some(format) = &formats.date {
+            csv = csv.with_date_format(format.clone());
+        }
+        if let Some(format) = &formats.datetime {
+            csv = csv.with_datetime_format(format.clone());
+        }
+        if let Some(format) = &formats.timestamp {
+            csv = csv
+                .with_timestamp_format(format.clone())
+                .with_timestamp_tz_format(format.clone());
+        }
+        if let Some(format) = &formats.time {
+            csv = csv.with_time_format(format.clone());
+        }
+        let json = json();
+        Ok(match format {
+            Format::Table if options.vertical => {
+                Self::Vertical(VerticalWriter::new(out, schema, options))
+            }
+            Format::Table => Self::Table(TableWriter::new(out, schema, options)),
+            Format::Csv => Self::Csv(Box::new(csv.build(out)), formats.clone()),
+            Format::Tsv => Self::Csv(
+                Box::new(csv.with_delimiter(b'\t').build(out)),
+                formats.clone(),
+            ),
+            Format::Json => Self::Json {
+                out: Some(out),
+                writer: None,
+            },
+            Format::Ndjson => Self::Ndjson(json.build(out)),
+        })
+    }
+
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match self {
+            Self::Table(w) => w.write(batch)?,
+            Self::Vertical(w) => w.write(batch, None, None)?,
+            Self::Csv(w, formats) => w.write(&formats.round_floats(batch)?)?,
+            Self::Json { out, writer } => {
+                if batch.num_rows() > 0 {
+                    let writer = match writer {
+                        Some(writer) => writer,
+                        None => writer.insert(json().build::<_, JsonArray>(out.take().unwrap())),
+                    };
+                    writer.write(batch)?;
+                }
+            }
+            Self::Ndjson(w) => w.write(batch)?,
+        }
+        Ok(())
+    }
+
+    /// Writes `batch`, whose rows are at the specified indices of `file`,
+    /// named when the dataset has several. Only vertical tables show them.
+    pub fn write_numbered(
+        &mut self,
+        batch: &RecordBatch,
+        rows: &[usize],
+        file: Option<&str>,
+    ) -> Result<()> {
+        match self {
+            Self::Vertical(w) => w.write(batch, Some(rows), file),
+            w => w.write(batch),
+        }
+    }
+
+    pub fn finish(self) -> Result<()> {
+        let mut out = match self {
+            Self::Table(w) => return w.finish(),
+            Self::Vertical(w) => w.into_inner(),
+            Self::Csv(w, _) => w.into_inner(),
+            Self::Json {
+                writer: Some(mut writer),
+                ..
+            } => {
+                writer.finish()?;
+                let mut out = writer.into_inner();
+                writeln!(out)?;
+                out
+            }
+            Self::Json { out, .. } => {
+                let mut out = out.unwrap();
+                writeln!(out, "[]")?;
+                out
+            }
+            Self::Ndjson(w) => w.into_inner(),
+        };
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// The builder of JSON writers.
+fn json() -> arrow_json::WriterBuilder {
+    arrow_json::WriterBuilder::new().with_explicit_nulls(true)
+}
+
+/// Number of rows buffered before the column widths are fixed.
+const SAMPLE_ROWS: usize = 1000;
+
+/// Writes record batches as a table, as soon as they are decoded.
+///
+/// The column widths are worked out from the header and the first
+/// [`SAMPLE_ROWS`] rows. Later cells that don't fit are wrapped, or truncated
+/// when the table is fitted to the terminal.
+pub struct TableWriter<W: Write> {
+    out: W,
+    header: Vec<String>,
+    sample: Vec<Vec<String>>,
+    widths: Option<Vec<usize>>,
+    max_width: Option<usize>,
+    fit_width: Option<usize>,
+    formats: Formats,
+}
+
+impl<W: Write> TableWriter<W> {
+    pub fn new(out: W, schema: &Schema, options: &Options) -> Self {
+        Self {
+            out,
+            header: schema.fields().iter().map(|f| f.name().clone()).collect(),
+            sample: vec![],
+            widths: None,
+            max_width: options.max_width,
+            fit_width: options.fit_width,
+            formats: options.formats.clone(),
+        }
+    }
+
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let batch = self.formats.round_floats(batch)?;
+        // Rows are written while the formatters borrow the formats.
+        let formats = self.formats.clone();
+        let options = formats.options();
+        let columns = batch
+            .columns()
+            .iter()
+            .map(|c| ArrayFormatter::try_new(c, &options))
+            .collect::<Result<Vec<_>, _>>()?;
+        // No cell is wider than the terminal.
+        let max = min_width(self.max_width, self.fit_width);
+        for i in 0..batch.num_rows() {
+            let row = columns
+                .iter()
+                .map(|col| format_value(col, i, max))
+                .collect::<Result<Vec<_>>>()?;
+            if self.widths.is_some() {
+                self.write_row(&row)?;
+            } else {
+                self.sample.push(row);
+                if self.sample.len() >= SAMPLE_ROWS {
+                    self.flush_sample()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        if self.header.is_empty() {
+            return Ok(());
+        }
+        if self.widths.is_none() {
+            self.flush_sample()?;
+        }
+        self.write_border('╰', '─', '┴', '╯')?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn flush_sample(&mut self) -> Result<()> {
+        let mut widths = self.header.iter().map(|s| width(s)).collect::<Vec<_>>();
+        for row in &self.sample {
+            for (w, cell) in widths.iter_mut().zip(row) {
+                *w = (*w).max(width(cell));
+            }
+        }
+        if let Some(total) = self.fit_width {
+            widths = arrange(&widths, total);
+        }
+        self.widths = Some(widths);
+        if self.header.is_empty() {
+            return Ok(());
+        }
+        self.write_border('╭', '─', '┬', '╮')?;
+        let header = std::mem::take(&mut self.header);
+        self.write_row(&header)?;
+        self.header = header;
+        self.write_border('╞', '═', '╪', '╡')?;
+        for row in std::mem::take(&mut self.sample) {
+            self.write_row(&row)?;
+        }
+        Ok(())
+    }
+
+    fn write_border(&mut self, left: char, line: char, sep: char, right: char) -> Result<()> {
+        let widths = self.widths.as_deref().unwrap_or_default();
+        let mut s = String::new();
+        s.push(left);
+        for (i, w) in widths.iter().enumerate() {
+            if i > 0 {
+                s.push(sep);
+            }
+            s.extend(std::iter::repeat_n(line, w + 2));
+        }
+        s.push(right);
+        writeln!(self.out, "{}", s)?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[String]) -> Result<()> {
+        if row.is_empty() {
+            return Ok(());
+        }
+        let widths = self.widths.as_deref().unwrap_or_default();
+        let cells = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, w)| match self.fit_width {
+                Some(_) => cell.split('\n').map(|line| truncate(line, *w)).collect(),
+                None => wrap(cell, *w),
+            })
+            .collect::<Vec<Vec<_>>>();
+        let height = cells.iter().map(|c| c.len()).max().unwrap_or(1);
+        for line in 0..height {
+            let mut s = String::from("│");
+            for (i, (cell, w)) in cells.iter().zip(widths).enumerate() {
+                if i > 0 {
+                    s.push('┆');
+                }
+                let text = cell.get(line).map(String::as_str).unwrap_or_default();
+                s.push(' ');
+                s.push_str(text);
+                s.extend(std::iter::repeat_n(' ', w.saturating_sub(width(text)) + 1));
+            }
+            s.push('│');
+            writeln!(self.out, "{}", s)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes each row as a block of `column | value` lines, like the expanded
+/// display of psql.
+pub struct VerticalWriter<W: Write> {
+    out: W,
+    names: Vec<String>,
+    /// Index of the next row, for rows written without one.
+    next: usize,
+    max_width: Option<usize>,
+    fit_width: Option<usize>,
+    formats: Formats,
+}
+
+impl<W: Write> VerticalWriter<W> {
+    pub fn new(out: W, schema: &Schema, options: &Options) -> Self {
+        Self {
+            out,
+            names: schema.fields().iter().map(|f| f.name().clone()).collect(),
+            next: 0,
+            max_width: options.max_width,
+            fit_width: options.fit_width,
+            formats: options.formats.clone(),
+        }
+    }
+
+    /// Writes the rows of `batch`, headed by their index, which is the
+    /// index in `file` when it is named.
+    pub fn write(
+        &mut self,
+        batch: &RecordBatch,
+        rows: Option<&[usize]>,
+        file: Option<&str>,
+    ) -> Result<()> {
+        let batch = self.formats.round_floats(batch)?;
+        let options = self.formats.options();
+        let columns = batch
+            .columns()
+            .iter()
+            .map(|c| ArrayFormatter::try_new(c, &options))
+            .collect::<Result<Vec<_>, _>>()?;
+        let name_width = self.names.iter().map(|n| width(n)).max().unwrap_or(0);
+        let fit_width = self
+            .fit_width
+            .map(|total| total.saturating_sub(name_width + 3).max(MIN_WIDTH));
+        let max = min_width(self.max_width, fit_width);
+        for i in 0..batch.num_rows() {
+            let row = rows.map_or(self.next, |rows| rows[i]);
+            self.next = row + 1;
+            let values = columns
+                .iter()
+                .map(|col| format_value(col, i, max))
+                .collect::<Result<Vec<_>>>()?;
+            let value_width = values.iter().map(|v| width(v)).max().unwrap_or(0);
+            let mut separator = match file {
+                Some(file) => format!("-[ RECORD {} in {} ]", row, file),
+                None => format!("-[ RECORD {} ]", row),
+            };
+            let title_width = width(&separator);
+            separator.extend(std::iter::repeat_n(
+                '-',
+                (name_width + 1).saturating_sub(title_width),
+            ));
+            separator.push('+');
+            separator.extend(std::iter::repeat_n('-', value_width + 1));
+            writeln!(self.out, "{}", separator)?;
+            for (name, value) in self.names.iter().zip(&values) {
+                for (j, line) in value.split('\n').enumerate() {
+                    let label = if j == 0 { name.as_str() } else { "" };
+                    let padding = name_width - width(label);
+                    writeln!(self.out, "{}{:padding$} | {}", label, "", line)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Narrowest a column gets when a table is fitted to the terminal.
+const MIN_WIDTH: usize = 3;
+
+/// The smaller of two optional widths.
+fn min_width(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Shrinks the widths of columns so that a table fits in `total` terminal
+/// columns, like the dynamic arrangement of comfy_table: columns narrower
+/// than an even share of the space left keep their width, and the others
+/// split what remains.
+fn arrange(widths: &[usize], total: usize) -> Vec<usize> {
+    // Each column has a border and a space on each side.
+    let mut available = total.saturating_sub(3 * widths.len() + 1);
+    if widths.iter().sum::<usize>() <= available {
+        return widths.to_vec();
+    }
+    let mut arranged = widths.to_vec();
+    let mut remaining = (0..widths.len()).collect::<Vec<_>>();
+    while !remaining.is_empty() {
+        let share = available / remaining.len();
+        let (fit, rest): (Vec<_>, Vec<_>) = remaining.iter().partition(|&&i| widths[i] <= share);
+        if fit.is_empty() {
+            let extra = available % rest.len();
+            for (k, &i) in rest.iter().enumerate() {
+                arranged[i] = (share + usize::from(k < extra)).max(MIN_WIDTH);
+            }
+            break;
+        }
+        for &i in &fit {
+            available -= widths[i];
+        }
+        remaining = rest;
+    }
+    arranged
+}
+
+/// Collects formatted text up to a width, failing past it so that long
+/// values are never formatted in full.
+struct Truncating {
+    text: String,
+    width: usize,
+    max: usize,
+    truncated: bool,
+}
+
+impl fmt::Write for Truncating {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let w = c.width().unwrap_or(0);
+            if self.width + w > self.max {
+                self.truncated = true;
+                return Err(fmt::Error);
+            }
+            self.text.push(c);
+            self.width += w;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the value at `row`, truncated to `max` terminal columns.
+fn format_value(column: &ArrayFormatter, row: usize, max: Option<usize>) -> Result<String> {
+    let Some(max) = max else {
+        return Ok(column.value(row).try_to_string()?);
+    };
+    let mut out = Truncating {
+        text: String::new(),
+        width: 0,
+        max,
+        truncated: false,
+    };
+    match column.value(row).write(&mut out) {
+        Ok(()) => Ok(out.text),
+        Err(_) if out.truncated => Ok(ellipsize(out.text, max)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Cuts a line to `max` terminal columns, ending it with an ellipsis.
+fn truncate(line: &str, max: usize) -> String {
+    if width(line) <= max {
+        return line.to_string();
+    }
+    ellipsize(line.to_string(), max)
+}
+
+/// Replaces the end of `text` with an ellipsis, so that it fits in `max`
+/// terminal columns.
+fn ellipsize(mut text: String, max: usize) -> String {
+    let mut text_width = text.chars().filter_map(|c| c.width()).sum::<usize>();
+    while text_width + 1 > max {
+        match text.pop() {
+            Some(c) => text_width -= c.width().unwrap_or(0),
+            None => break,
+        }
+    }
+    text.push('…');
+    text
+}
+
+/// Display width of a string in terminal columns.
+fn width(s: &str) -> usize {
+    s.lines()
+        .map(|line| line.chars().filter_map(|c| c.width()).sum())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Splits a cell into lines no wider than `max`.
+fn wrap(s: &str, max: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for line in s.split('\n') {
+        let mut current = String::new();
+        let mut current_width = 0;
+        for c in line.chars() {
+            let w = c.width().unwrap_or(0);
+            if current_width + w > max && !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            current.push(c);
+            current_width += w;
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Float64Array, Int32Array};
+
+    #[test]
+    fn rounds_nested_floats() {
+        let item = StructArray::from(vec![
+            (
+                Arc::new(Field::new("qty", DataType::Int32, false)),
+                Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("price", DataType::Float64, false)),
+                Arc::new(Float64Array::from(vec![1.23456, 2.5])) as ArrayRef,
+            ),
+        ]);
+        let prices = ListArray::from_iter_primitive::<Float64Type, _, _>([
+            Some(vec![Some(0.125), None]),
+            None,
+        ]);
+        let batch = RecordBatch::try_from_iter([
+            ("item", Arc::new(item) as ArrayRef),
+            ("prices", Arc::new(prices) as ArrayRef),
+        ])
+        .unwrap();
+        let formats = Formats {
+            null: String::new(),
+            date: None,
+            datetime: None,
+            timestamp: None,
+            time: None,
+            float_precision: Some(2),
+            duration: DurationFormat::ISO8601,
+        };
+        let rounded = formats.round_floats(&batch).unwrap();
+        let item = rounded.column(0).as_struct();
+        assert_eq!(item.column(0).data_type(), &DataType::Int32);
+        let strings = |array: &ArrayRef| {
+            let array = array.as_string::<i32>();
+            array
+                .iter()
+                .map(|v| v.map(str::to_string))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            strings(item.column(1)),
+            [Some("1.23".into()), Some("2.50".into())]
+        );
+        let prices = rounded.column(1).as_list::<i32>();
+        assert_eq!(strings(prices.values()), [Some("0.12".into()), None]);
+        assert!(prices.is_null(1));
+        let schema = rounded.schema();
+        assert_eq!(schema.field(0).data_type(), item.data_type());
+        assert_eq!(schema.field(1).data_type(), prices.data_type());
+    }
+
+    #[test]
+    fn ellipsizes_on_char_boundaries() {
+        assert_eq!(ellipsize("abcdef".to_string(), 4), "abc…");
+        assert_eq!(ellipsize("héllo".to_string(), 3), "hé…");
+        // Wide characters take two columns, so none is cut in half.
+        assert_eq!(ellipsize("日本語".to_string(), 4), "日…");
+        assert_eq!(ellipsize("日本語".to_string(), 5), "日本…");
+        assert_eq!(ellipsize("a".to_string(), 1), "…");
+        assert_eq!(truncate("short", 5), "short");
+        assert_eq!(truncate("longer", 5), "long…");
+        assert_eq!(width(&truncate("a日本語", 4)), 4);
+    }
+
+    #[test]
+    fn formats_values_up_to_the_width() {
+        let array = Arc::new(StringArray::from(vec!["0123456789", "日本語", "ab"])) as ArrayRef;
+        let options = FormatOptions::default();
+        let column = ArrayFormatter::try_new(&array, &options).unwrap();
+        let value = |row, max| format_value(&column, row, max).unwrap();
+        assert_eq!(value(0, None), "0123456789");
+        assert_eq!(value(0, Some(5)), "0123…");
+        assert_eq!(value(0, Some(10)), "0123456789");
+        assert_eq!(value(1, Some(5)), "日本…");
+        assert_eq!(value(2, Some(2)), "ab");
+    }
+
+    #[test]
+    fn wraps_lines() {
+        assert_eq!(wrap("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(wrap("ab\ncdef", 3), ["ab", "cde", "f"]);
+        assert_eq!(wrap("日本語", 3), ["日", "本", "語"]);
+        assert_eq!(wrap("", 3), [""]);
+        // A character wider than the line still gets one.
+        assert_eq!(wrap("日本", 1), ["日", "本"]);
+    }
+
+    #[test]
+    fn arranges_columns_to_fit() {
+        // 3 columns take 10 columns of borders and padding.
+        assert_eq!(arrange(&[5, 5, 5], 25), [5, 5, 5]);
+        assert_eq!(arrange(&[2, 30, 40], 40), [2, 14, 14]);
+        assert_eq!(arrange(&[2, 30, 40], 41), [2, 15, 14]);
+        assert_eq!(arrange(&[10, 10], 5), [MIN_WIDTH, MIN_WIDTH]);
+    }
+
+    #[test]
+    fn heads_records_with_their_file() {
+        let batch = RecordBatch::try_from_iter([(
+            "id",
+            Arc::new(Int32Array::from(vec![7, 8])) as ArrayRef,
+        )])
+        .unwrap();
+        let options = Options {
+            header: true,
+            vertical: true,
+            max_width: None,
+            fit_width: None,
+            formats: Formats {
+                null: String::new(),
+                date: None,
+                datetime: None,
+                timestamp: None,
+                time: None,
+                float_precision: None,
+                duration: DurationFormat::ISO8601,
+            },
+        };
+        let mut writer = VerticalWriter::new(vec![], &batch.schema(), &options);
+        writer
+            .write(&batch, Some(&[3, 5]), Some("a.parquet"))
+            .unwrap();
+        writer.write(&batch, Some(&[0, 1]), None).unwrap();
+        // Rows without indices follow the last one written.
+        writer.write(&batch, None, None).unwrap();
+        let out = String::from_utf8(writer.into_inner()).unwrap();
+        let headings = out
+            .lines()
+            .filter(|l| l.starts_with("-["))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headings,
+            [
+                "-[ RECORD 3 in a.parquet ]+--",
+                "-[ RECORD 5 in a.parquet ]+--",
+                "-[ RECORD 0 ]+--",
+                "-[ RECORD 1 ]+--",
+                "-[ RECORD 2 ]+--",
+                "-[ RECORD 3 ]+--",
+            ]
+        );
+        assert!(out.starts_with("-[ RECORD 3 in a.parquet ]+--\nid | 7\n"));
+    }
+}
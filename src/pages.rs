@@ -0,0 +1,316 @@
+use crate::{
+    input::{chunk_start, Input},
+    output::{optional, table},
+    stats::{leaf_type, Physical},
+};
+use comfy_table::Cell;
+use parquet::{
+    basic::{Encoding, PageType},
+    errors::Result,
+    file::{
+        metadata::{ColumnChunkMetaData, ParquetMetaData},
+        page_index::index::{Index, PageIndex},
+    },
+};
+use serde_json::{json, Value};
+
+/// A page of a column chunk, from its header and the page index.
+struct Page {
+    row_group: usize,
+    column: String,
+    page_type: PageType,
+    encoding: Option<Encoding>,
+    values: i32,
+    compressed: i32,
+    uncompressed: i32,
+    /// Unknown for data pages v1 of repeated columns without an offset
+    /// index.
+    first_row: Option<i64>,
+    /// From the column index, for data pages.
+    min: Option<String>,
+    max: Option<String>,
+    nulls: Option<i64>,
+    null_page: Option<bool>,
+}
+
+/// The minimum, maximum and null count of a page in the column index.
+fn page_bounds(
+    index: &Index,
+    page: usize,
+) -> Option<(Option<Physical<'_>>, Option<Physical<'_>>, Option<i64>)> {
+    fn get<'a, T>(
+        indexes: &'a [PageIndex<T>],
+        page: usize,
+        f: impl Fn(&'a T) -> Physical<'a>,
+    ) -> Option<(Option<Physical<'a>>, Option<Physical<'a>>, Option<i64>)> {
+        let PageIndex {
+            min,
+            max,
+            null_count,
+            ..
+        } = indexes.get(page)?;
+        Some((min.as_ref().map(&f), max.as_ref().map(&f), *null_count))
+    }
+    match index {
+        Index::NONE => None,
+        Index::BOOLEAN(i) => get(&i.indexes, page, |v| Physical::Bool(*v)),
+        Index::INT32(i) => get(&i.indexes, page, |v| Physical::Int32(*v)),
+        Index::INT64(i) => get(&i.indexes, page, |v| Physical::Int64(*v)),
+        Index::INT96(i) => get(&i.indexes, page, Physical::Int96),
+        Index::FLOAT(i) => get(&i.indexes, page, |v| Physical::Float(*v)),
+        Index::DOUBLE(i) => get(&i.indexes, page, |v| Physical::Double(*v)),
+        Index::BYTE_ARRAY(i) => get(&i.indexes, page, |v| Physical::Bytes(v.data())),
+        Index::FIXED_LEN_BYTE_ARRAY(i) => get(&i.indexes, page, |v| Physical::Bytes(v.data())),
+    }
+}
+
+/// Reads the pages of a column chunk.
+fn chunk_pages(
+    input: &Input,
+    metadata: &ParquetMetaData,
+    row_group: usize,
+    column: usize,
+    chunk: &ColumnChunkMetaData,
+) -> Result<Vec<Page>> {
+    let data_type = leaf_type(chunk.column_descr());
+    let locations = metadata
+        .offset_index()
+        .and_then(|i| i.get(row_group)?.get(column))
+        .map(|i| i.page_locations());
+    let index = metadata
+        .column_index()
+        .and_then(|i| i.get(row_group)?.get(column));
+    let repeated = chunk.column_descr().max_rep_level() > 0;
+    let start = chunk_start(chunk) as u64;
+    let end = start + chunk.compressed_size() as u64;
+    let mut pages = vec![];
+    let mut data_pages = 0;
+    // Without an offset index, the first rows are worked out from the
+    // number of rows of each page, when known.
+    let mut next_row = Some(0);
+    for page in input.page_headers(start, end) {
+        let header = page?.header;
+        let page_type = PageType::try_from(header.type_)?;
+        let (encoding, values, rows) = if let Some(h) = &header.dictionary_page_header {
+            (Some(h.encoding), h.num_values, None)
+        } else if let Some(h) = &header.data_page_header {
+            (
+                Some(h.encoding),
+                h.num_values,
+                (!repeated).then_some(h.num_values),
+            )
+        } else if let Some(h) = &header.data_page_header_v2 {
+            (Some(h.encoding), h.num_values, Some(h.num_rows))
+        } else {
+            (None, 0, None)
+        };
+        let mut page = Page {
+            row_group,
+            column: chunk.column_path().string(),
+            page_type,
+            encoding: encoding.map(Encoding::try_from).transpose()?,
+            values,
+            compressed: header.compressed_page_size,
+            uncompressed: header.uncompressed_page_size,
+            first_row: None,
+            min: None,
+            max: None,
+            nulls: None,
+            null_page: None,
+        };
+        if matches!(page_type, PageType::DATA_PAGE | PageType::DATA_PAGE_V2) {
+            page.first_row = match locations.and_then(|l| l.get(data_pages)) {
+                Some(location) => Some(location.first_row_index),
+                None => next_row,
+            };
+            next_row = match (next_row, rows) {
+                (Some(row), Some(rows)) => Some(row + rows as i64),
+                _ => None,
+            };
+            if let Some((min, max, nulls)) = index.and_then(|i| page_bounds(i, data_pages)) {
+                page.null_page = Some(min.is_none() && max.is_none());
+                page.min = min.map(|v| v.format(&data_type));
+                page.max = max.map(|v| v.format(&data_type));
+                page.nulls = nulls;
+            }
+            data_pages += 1;
+        }
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+/// Reads the pages of the specified leaf columns.
+///
+/// `metadata` should include the page index when the file has one.
+fn collect(input: &Input, metadata: &ParquetMetaData, leaves: &[usize]) -> Result<Vec<Page>> {
+    let mut pages = vec![];
+    for (i, rg) in metadata.row_groups().iter().enumerate() {
+        for &j in leaves {
+            pages.extend(chunk_pages(input, metadata, i, j, rg.column(j))?);
+        }
+    }
+    Ok(pages)
+}
+
+/// Prints the pages of the specified leaf columns.
+pub fn print(
+    input: &Input,
+    metadata: &ParquetMetaData,
+    leaves: &[usize],
+    fit_width: Option<usize>,
+) -> Result<()> {
+    let rows = collect(input, metadata, leaves)?.into_iter().map(|p| {
+        vec![
+            Cell::new(p.row_group),
+            Cell::new(p.column),
+            Cell::new(p.page_type),
+            Cell::new(optional(p.encoding)),
+            Cell::new(p.values),
+            Cell::new(p.compressed),
+            Cell::new(p.uncompressed),
+            Cell::new(optional(p.first_row)),
+            Cell::new(optional(p.min)),
+            Cell::new(optional(p.max)),
+            Cell::new(optional(p.nulls)),
+            Cell::new(optional(p.null_page)),
+        ]
+    });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec![
+                "row group",
+                "column",
+                "page type",
+                "encoding",
+                "values",
+                "compressed size",
+                "uncompressed size",
+                "first row",
+                "min",
+                "max",
+                "nulls",
+                "null page",
+            ])
+            .add_rows(rows)
+    );
+    Ok(())
+}
+
+/// Converts the pages of the specified leaf columns to JSON.
+pub fn to_json(input: &Input, metadata: &ParquetMetaData, leaves: &[usize]) -> Result<Value> {
+    let pages = collect(input, metadata, leaves)?
+        .into_iter()
+        .map(|p| {
+            json!({
+                "row_group": p.row_group,
+                "column": p.column,
+                "page_type": p.page_type.to_string(),
+                "encoding": p.encoding.map(|e| e.to_string()),
+                "num_values": p.values,
+                "compressed_size": p.compressed,
+                "uncompressed_size": p.uncompressed,
+                "first_row_index": p.first_row,
+                "min": p.min,
+                "max": p.max,
+                "null_count": p.nulls,
+                "null_page": p.null_page,
+            })
+        })
+        .collect();
+    Ok(Value::Array(pages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use arrow_array::{types::Int64Type, ArrayRef, Int64Array, ListArray, RecordBatch};
+    use parquet::{
+        arrow::arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions},
+        file::properties::{WriterProperties, WriterVersion},
+    };
+    use std::sync::Arc;
+
+    /// The pages of the first column of `batch`, written in pages of about 25
+    /// rows, read with or without the page index.
+    fn pages(batch: &RecordBatch, version: WriterVersion, page_index: bool) -> Vec<Page> {
+        let props = WriterProperties::builder()
+            .set_writer_version(version)
+            .set_dictionary_enabled(false)
+            .set_data_page_row_count_limit(25)
+            .set_write_batch_size(25)
+            .build();
+        let input = Input::Memory(testing::write(batch, props));
+        let options = ArrowReaderOptions::new().with_page_index(page_index);
+        let metadata = ArrowReaderMetadata::load(&input, options).unwrap();
+        let metadata = metadata.metadata();
+        assert_eq!(metadata.offset_index().is_some(), page_index);
+        collect(&input, metadata, &[0]).unwrap()
+    }
+
+    fn first_rows(pages: &[Page]) -> Vec<Option<i64>> {
+        pages.iter().map(|p| p.first_row).collect()
+    }
+
+    fn flat() -> RecordBatch {
+        let ids = Arc::new(Int64Array::from_iter_values(0..100)) as ArrayRef;
+        RecordBatch::try_from_iter([("id", ids)]).unwrap()
+    }
+
+    /// Lists of two values, so that pages hold more values than rows.
+    fn repeated() -> RecordBatch {
+        let lists = ListArray::from_iter_primitive::<Int64Type, _, _>(
+            (0..100).map(|i| Some(vec![Some(i), Some(i)])),
+        );
+        RecordBatch::try_from_iter([("l", Arc::new(lists) as ArrayRef)]).unwrap()
+    }
+
+    #[test]
+    fn reads_first_rows_from_the_offset_index() {
+        for version in [WriterVersion::PARQUET_1_0, WriterVersion::PARQUET_2_0] {
+            let flat = pages(&flat(), version, true);
+            assert_eq!(first_rows(&flat), [Some(0), Some(25), Some(50), Some(75)]);
+            let bounds = flat
+                .iter()
+                .map(|p| (p.min.as_deref(), p.max.as_deref(), p.nulls))
+                .collect::<Vec<_>>();
+            assert_eq!(bounds[1], (Some("25"), Some("49"), Some(0)));
+            assert!(flat.iter().all(|p| p.null_page == Some(false)));
+            let repeated = pages(&repeated(), version, true);
+            assert_eq!(
+                first_rows(&repeated),
+                [Some(0), Some(26), Some(52), Some(78)]
+            );
+        }
+    }
+
+    #[test]
+    fn counts_rows_without_an_offset_index() {
+        let v1 = pages(&flat(), WriterVersion::PARQUET_1_0, false);
+        assert!(v1.iter().all(|p| p.page_type == PageType::DATA_PAGE));
+        assert!(v1.iter().all(|p| p.min.is_none() && p.null_page.is_none()));
+        let indexed = pages(&flat(), WriterVersion::PARQUET_1_0, true);
+        assert_eq!(first_rows(&v1), first_rows(&indexed));
+        let v2 = pages(&flat(), WriterVersion::PARQUET_2_0, false);
+        assert!(v2.iter().all(|p| p.page_type == PageType::DATA_PAGE_V2));
+        assert_eq!(first_rows(&v2), first_rows(&indexed));
+        // Pages v2 give their number of rows, which differs from the number
+        // of values of repeated columns.
+        let v2 = pages(&repeated(), WriterVersion::PARQUET_2_0, false);
+        assert_eq!(
+            v2.iter().map(|p| p.values).collect::<Vec<_>>(),
+            [52, 52, 52, 44]
+        );
+        let indexed = pages(&repeated(), WriterVersion::PARQUET_2_0, true);
+        assert_eq!(first_rows(&v2), first_rows(&indexed));
+    }
+
+    #[test]
+    fn loses_track_of_rows_of_repeated_pages_v1() {
+        let pages = pages(&repeated(), WriterVersion::PARQUET_1_0, false);
+        assert!(pages.iter().all(|p| p.page_type == PageType::DATA_PAGE));
+        assert_eq!(first_rows(&pages), [Some(0), None, None, None]);
+    }
+}
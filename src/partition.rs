@@ -0,0 +1,261 @@
+use crate::{dataset::Found, prune::PruningStatistics};
+use arrow_array::{
+    new_null_array, Array, ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray,
+    UInt32Array, UInt64Array,
+};
+use arrow_schema::{ArrowError, DataType, Field, Fields, Schema};
+use parquet::errors::{ParquetError, Result};
+use std::{ffi::OsStr, path::Path, sync::Arc};
+
+/// Value Hive writes for a null partition.
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Hive-style partitions, parsed from the `key=value` directories between
+/// each input directory and its files.
+pub struct Partitions {
+    pub fields: Fields,
+    /// One array per field, holding the value of each file.
+    pub columns: Vec<ArrayRef>,
+    num_files: usize,
+}
+
+impl Partitions {
+    /// Parses the partitions of `files`. The type of each key is inferred
+    /// from its values: integers, then floats, then strings.
+    pub fn new(files: &[Found]) -> Result<Self> {
+        let parsed = files
+            .iter()
+            .map(|file| segments(&file.relative))
+            .collect::<Vec<_>>();
+        let Some(first) = parsed.first() else {
+            return Ok(Self {
+                fields: Fields::empty(),
+                columns: vec![],
+                num_files: 0,
+            });
+        };
+        let keys = first.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+        for (file, segments) in files.iter().zip(&parsed) {
+            if !segments.iter().map(|(k, _)| k).eq(&keys) {
+                return Err(ParquetError::General(format!(
+                    "`{}` isn't partitioned by {}",
+                    file.path.display(),
+                    if keys.is_empty() {
+                        "nothing".to_string()
+                    } else {
+                        keys.join(", ")
+                    }
+                )));
+            }
+        }
+        let mut fields = vec![];
+        let mut columns = vec![];
+        for (i, key) in keys.into_iter().enumerate() {
+            let values = parsed
+                .iter()
+                .map(|segments| segments[i].1.as_deref())
+                .collect::<Vec<_>>();
+            let column = infer(&values);
+            fields.push(Field::new(
+                key,
+                column.data_type().clone(),
+                column.null_count() > 0,
+            ));
+            columns.push(column);
+        }
+        Ok(Self {
+            fields: fields.into(),
+            columns,
+            num_files: files.len(),
+        })
+    }
+
+    /// The values of the file at `index`, as single element arrays.
+    pub fn values(&self, index: usize) -> Vec<ArrayRef> {
+        self.columns.iter().map(|c| c.slice(index, 1)).collect()
+    }
+
+    fn column(&self, name: &str) -> Option<&ArrayRef> {
+        let (i, _) = self.fields.find(name)?;
+        Some(&self.columns[i])
+    }
+}
+
+/// The `key=value` directories of `path`, relative to the input directory,
+/// from the outermost. Null values are `None`.
+fn segments(path: &Path) -> Vec<(String, Option<String>)> {
+    let Some(parent) = path.parent() else {
+        return vec![];
+    };
+    parent
+        .components()
+        .filter_map(|c| {
+            let (key, value) = split(c.as_os_str())?;
+            let value = unescape(value);
+            let value = (!value.is_empty() && value != DEFAULT_PARTITION).then_some(value);
+            Some((unescape(key), value))
+        })
+        .collect()
+}
+
+/// Whether the directory `name` is a `key=value` partition.
+pub fn is_partition(name: &OsStr) -> bool {
+    split(name).is_some()
+}
+
+fn split(name: &OsStr) -> Option<(&str, &str)> {
+    let (key, value) = name.to_str()?.split_once('=')?;
+    (!key.is_empty()).then_some((key, value))
+}
+
+/// Decodes the `%XX` escapes Hive uses for special characters.
+fn unescape(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn infer(values: &[Option<&str>]) -> ArrayRef {
+    if values.iter().all(Option::is_none) {
+        return new_null_array(&DataType::Utf8, values.len());
+    }
+    let ints = values
+        .iter()
+        .map(|v| v.map(str::parse::<i64>).transpose())
+        .collect::<Result<Int64Array, _>>();
+    if let Ok(ints) = ints {
+        return Arc::new(ints);
+    }
+    let floats = values
+        .iter()
+        .map(|v| v.map(str::parse::<f64>).transpose())
+        .collect::<Result<Float64Array, _>>();
+    if let Ok(floats) = floats {
+        return Arc::new(floats);
+    }
+    Arc::new(values.iter().copied().collect::<StringArray>())
+}
+
+/// Each file is a container. Its partition values are both the minimum and
+/// the maximum, and are either null for all of its rows or for none, so the
+/// null count is given per single row.
+impl PruningStatistics for Partitions {
+    fn num_containers(&self) -> usize {
+        self.num_files
+    }
+
+    fn min_values(&self, column: &str) -> Option<ArrayRef> {
+        self.column(column).cloned()
+    }
+
+    fn max_values(&self, column: &str) -> Option<ArrayRef> {
+        self.column(column).cloned()
+    }
+
+    fn null_counts(&self, column: &str) -> Option<UInt64Array> {
+        let column = self.column(column)?;
+        Some(
+            (0..column.len())
+                .map(|i| Some(column.is_null(i) as u64))
+                .collect(),
+        )
+    }
+
+    fn row_counts(&self) -> Option<UInt64Array> {
+        Some(UInt64Array::from(vec![1; self.num_containers()]))
+    }
+}
+
+/// Appends the partition columns of a file to `batch`.
+pub fn append(
+    batch: &RecordBatch,
+    fields: &Fields,
+    values: &[ArrayRef],
+) -> Result<RecordBatch, ArrowError> {
+    let mut schema_fields = batch.schema().fields().to_vec();
+    schema_fields.extend(fields.iter().cloned());
+    let mut columns = batch.columns().to_vec();
+    let indices = UInt32Array::from(vec![0; batch.num_rows()]);
+    for value in values {
+        columns.push(arrow_select::take::take(value, &indices, None)?);
+    }
+    RecordBatch::try_new_with_options(
+        Arc::new(Schema::new(schema_fields)),
+        columns,
+        &arrow_array::RecordBatchOptions::new().with_row_count(Some(batch.num_rows())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{cast::AsArray, types::Int64Type};
+
+    fn some(key: &str, value: &str) -> (String, Option<String>) {
+        (key.to_string(), Some(value.to_string()))
+    }
+
+    #[test]
+    fn parses_segments() {
+        assert_eq!(
+            segments(Path::new("year=2024/month=05/part-0.parquet")),
+            [some("year", "2024"), some("month", "05")]
+        );
+        assert_eq!(
+            segments(Path::new("sub/a=1/=2/b/c=x=y/part-0.parquet")),
+            [some("a", "1"), some("c", "x=y")]
+        );
+        assert_eq!(
+            segments(Path::new("a=/b=__HIVE_DEFAULT_PARTITION__/part-0.parquet")),
+            [("a".to_string(), None), ("b".to_string(), None)]
+        );
+        assert_eq!(
+            segments(Path::new("city%3Dname=New%20York/part-0.parquet")),
+            [some("city=name", "New York")]
+        );
+        assert_eq!(segments(Path::new("part-0.parquet")), []);
+        assert_eq!(segments(Path::new("")), []);
+    }
+
+    #[test]
+    fn unescapes() {
+        assert_eq!(unescape("a%2Fb%3a"), "a/b:");
+        assert_eq!(unescape("100%"), "100%");
+        assert_eq!(unescape("%zz%4"), "%zz%4");
+        assert_eq!(unescape("%C3%A9t%C3%A9"), "été");
+        assert_eq!(unescape("%FF"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn infers_types() {
+        let ints = infer(&[Some("1"), None, Some("-20")]);
+        assert_eq!(ints.data_type(), &DataType::Int64);
+        assert_eq!(ints.as_primitive::<Int64Type>().value(2), -20);
+        assert!(ints.is_null(1));
+        let floats = infer(&[Some("1"), Some("2.5"), Some("1e3")]);
+        assert_eq!(floats.data_type(), &DataType::Float64);
+        let strings = infer(&[Some("1"), Some("x"), None]);
+        assert_eq!(strings.as_string::<i32>().value(0), "1");
+        assert!(strings.is_null(2));
+        let nulls = infer(&[None, None]);
+        assert_eq!(nulls.data_type(), &DataType::Utf8);
+        assert_eq!(nulls.null_count(), 2);
+    }
+}
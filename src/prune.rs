@@ -0,0 +1,361 @@
+use crate::expr::{coerce, CmpOp, Expr, Literal, Value};
+use arrow_arith::boolean::{and, not, or};
+use arrow_array::{cast::AsArray, Array, ArrayRef, BooleanArray, UInt64Array};
+use arrow_ord::cmp;
+use arrow_schema::{DataType, Schema};
+use parquet::{
+    arrow::arrow_reader::statistics::StatisticsConverter, file::metadata::ParquetMetaData,
+};
+
+/// Statistics of a set of containers, such as the row groups of a file.
+pub trait PruningStatistics {
+    fn num_containers(&self) -> usize;
+
+    /// Minimum values of a column in each container, null when unknown.
+    fn min_values(&self, column: &str) -> Option<ArrayRef>;
+
+    /// Maximum values of a column in each container, null when unknown.
+    fn max_values(&self, column: &str) -> Option<ArrayRef>;
+
+    /// Null counts of a column in each container, null when unknown.
+    fn null_counts(&self, column: &str) -> Option<UInt64Array>;
+
+    /// Row counts of each container, null when unknown.
+    fn row_counts(&self) -> Option<UInt64Array>;
+}
+
+/// Statistics of the row groups of a Parquet file.
+pub struct RowGroupStatistics<'a> {
+    metadata: &'a ParquetMetaData,
+    schema: &'a Schema,
+}
+
+impl<'a> RowGroupStatistics<'a> {
+    pub fn new(metadata: &'a ParquetMetaData, schema: &'a Schema) -> Self {
+        Self { metadata, schema }
+    }
+
+    fn converter(&self, column: &str) -> Option<StatisticsConverter<'a>> {
+        StatisticsConverter::try_new(
+            column,
+            self.schema,
+            self.metadata.file_metadata().schema_descr(),
+        )
+        .ok()
+    }
+}
+
+impl PruningStatistics for RowGroupStatistics<'_> {
+    fn num_containers(&self) -> usize {
+        self.metadata.num_row_groups()
+    }
+
+    fn min_values(&self, column: &str) -> Option<ArrayRef> {
+        self.converter(column)?
+            .row_group_mins(self.metadata.row_groups())
+            .ok()
+    }
+
+    fn max_values(&self, column: &str) -> Option<ArrayRef> {
+        self.converter(column)?
+            .row_group_maxes(self.metadata.row_groups())
+            .ok()
+    }
+
+    fn null_counts(&self, column: &str) -> Option<UInt64Array> {
+        self.converter(column)?
+            .row_group_null_counts(self.metadata.row_groups())
+            .ok()
+    }
+
+    fn row_counts(&self) -> Option<UInt64Array> {
+        Some(
+            self.metadata
+                .row_groups()
+                .iter()
+                .map(|rg| Some(rg.num_rows() as u64))
+                .collect(),
+        )
+    }
+}
+
+/// Returns, for each container, whether it may hold rows matching `expr`.
+///
+/// A container is only ruled out when its statistics prove that no row
+/// matches; anything the statistics can't decide is kept.
+pub fn prune(expr: &Expr, stats: &dyn PruningStatistics) -> Vec<bool> {
+    let n = stats.num_containers();
+    match may_match(expr, stats) {
+        Some(result) => result.iter().map(|b| b.unwrap_or(true)).collect(),
+        None => vec![true; n],
+    }
+}
+
+/// Whether each container may match, or `None` if nothing is known.
+fn may_match(expr: &Expr, stats: &dyn PruningStatistics) -> Option<BooleanArray> {
+    match expr {
+        Expr::And(l, r) => match (may_match(l, stats), may_match(r, stats)) {
+            (Some(l), Some(r)) => and(&fill(l), &fill(r)).ok(),
+            (l, r) => l.or(r),
+        },
+        Expr::Or(l, r) => or(&fill(may_match(l, stats)?), &fill(may_match(r, stats)?)).ok(),
+        Expr::Not(e) => match e.as_ref() {
+            Expr::Column(c) => truth(c, false, stats),
+            e => may_match(&negate(e)?, stats),
+        },
+        Expr::Compare(l, op, r) => match (l.as_ref(), r.as_ref()) {
+            (Expr::Column(c), Expr::Literal(lit)) => compare(c, *op, lit, stats),
+            (Expr::Literal(lit), Expr::Column(c)) => compare(c, op.flip(), lit, stats),
+            _ => None,
+        },
+        Expr::InList(e, list, negated) => {
+            let op = if *negated { CmpOp::NotEq } else { CmpOp::Eq };
+            let combined = list
+                .iter()
+                .map(|item| Expr::Compare(e.clone(), op, Box::new(item.clone())))
+                .reduce(|l, r| {
+                    if *negated {
+                        Expr::And(Box::new(l), Box::new(r))
+                    } else {
+                        Expr::Or(Box::new(l), Box::new(r))
+                    }
+                })?;
+            may_match(&combined, stats)
+        }
+        Expr::IsNull(e, negated) => {
+            let Expr::Column(c) = e.as_ref() else {
+                return None;
+            };
+            let null_counts = stats.null_counts(c)?;
+            if *negated {
+                cmp::lt(&null_counts, &stats.row_counts()?).ok()
+            } else {
+                cmp::gt(&null_counts, &UInt64Array::new_scalar(0)).ok()
+            }
+        }
+        Expr::Column(c) => truth(c, true, stats),
+        Expr::Literal(_) | Expr::Like(..) => None,
+    }
+}
+
+/// Whether each container may hold a boolean column equal to `value`. Other
+/// columns are cast to booleans when evaluated, so `id` means that `id` isn't
+/// zero, which the statistics can't tell.
+fn truth(column: &str, value: bool, stats: &dyn PruningStatistics) -> Option<BooleanArray> {
+    if stats.min_values(column)?.data_type() != &DataType::Boolean {
+        return None;
+    }
+    compare(column, CmpOp::Eq, &Literal::Bool(value), stats)
+}
+
+/// The expression matching the rows `expr` doesn't, with the negation
+/// pushed down to the leaves.
+fn negate(expr: &Expr) -> Option<Expr> {
+    Some(match expr {
+        Expr::And(l, r) => Expr::Or(Box::new(negate(l)?), Box::new(negate(r)?)),
+        Expr::Or(l, r) => Expr::And(Box::new(negate(l)?), Box::new(negate(r)?)),
+        Expr::Not(e) => e.as_ref().clone(),
+        Expr::Compare(l, op, r) => Expr::Compare(l.clone(), op.negate(), r.clone()),
+        Expr::IsNull(e, negated) => Expr::IsNull(e.clone(), !negated),
+        Expr::InList(e, list, negated) => Expr::InList(e.clone(), list.clone(), !negated),
+        Expr::Column(c) => Expr::Not(Box::new(Expr::Column(c.clone()))),
+        Expr::Literal(_) | Expr::Like(..) => return None,
+    })
+}
+
+/// Whether each container may hold a value `v` with `v op lit`.
+fn compare(
+    column: &str,
+    op: CmpOp,
+    lit: &Literal,
+    stats: &dyn PruningStatistics,
+) -> Option<BooleanArray> {
+    if *lit == Literal::Null {
+        // Comparisons with null never match.
+        return Some(BooleanArray::from(vec![false; stats.num_containers()]));
+    }
+    // Parquet statistics leave NaN out, while comparisons order it above
+    // every other float, so containers can hold unseen NaNs matching these.
+    let floating = stats.min_values(column)?.data_type().is_floating();
+    if floating && (matches!(op, CmpOp::Gt | CmpOp::GtEq | CmpOp::NotEq) || is_nan(lit)) {
+        return None;
+    }
+    let min = || bound(stats.min_values(column)?, lit);
+    let max = || bound(stats.max_values(column)?, lit);
+    let result = match op {
+        CmpOp::Eq => {
+            let (min, max) = (min()?, max()?);
+            and(
+                &boolean(Value::compare(CmpOp::LtEq, &min.0, &min.1).ok()?),
+                &boolean(Value::compare(CmpOp::GtEq, &max.0, &max.1).ok()?),
+            )
+            .ok()?
+        }
+        CmpOp::NotEq => {
+            let (min, max) = (min()?, max()?);
+            let min_eq = boolean(Value::compare(CmpOp::Eq, &min.0, &min.1).ok()?);
+            let max_eq = boolean(Value::compare(CmpOp::Eq, &max.0, &max.1).ok()?);
+            not(&and(&min_eq, &max_eq).ok()?).ok()?
+        }
+        CmpOp::Lt | CmpOp::LtEq => {
+            let (min, lit) = min()?;
+            boolean(Value::compare(op, &min, &lit).ok()?)
+        }
+        CmpOp::Gt | CmpOp::GtEq => {
+            let (max, lit) = max()?;
+            boolean(Value::compare(op, &max, &lit).ok()?)
+        }
+    };
+    Some(result)
+}
+
+/// Whether the literal is a float NaN, or a string cast to one.
+fn is_nan(lit: &Literal) -> bool {
+    match lit {
+        Literal::Float(f) => f.is_nan(),
+        Literal::Str(s) => s.parse::<f64>().is_ok_and(|f| f.is_nan()),
+        _ => false,
+    }
+}
+
+/// Coerces a statistics array and a literal to a common type.
+fn bound(values: ArrayRef, lit: &Literal) -> Option<(Value, Value)> {
+    coerce(Value::Array(values), Value::Scalar(lit.to_array())).ok()
+}
+
+/// Replaces unknown results with `true`.
+fn fill(array: BooleanArray) -> BooleanArray {
+    if array.null_count() == 0 {
+        array
+    } else {
+        array.iter().map(|b| Some(b.unwrap_or(true))).collect()
+    }
+}
+
+fn boolean(value: Value) -> BooleanArray {
+    match value {
+        Value::Array(a) | Value::Scalar(a) => a.as_boolean().clone(),
+    }
+}
+
+/// Keeps the row groups of `row_groups` that may match `expr`.
+pub fn row_groups(
+    expr: &Expr,
+    metadata: &ParquetMetaData,
+    schema: &Schema,
+    row_groups: Vec<usize>,
+) -> Vec<usize> {
+    let keep = prune(expr, &RowGroupStatistics::new(metadata, schema));
+    row_groups.into_iter().filter(|&i| keep[i]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{expr::parse, testing};
+    use arrow_array::{BooleanArray, Float64Array, Int64Array, RecordBatch};
+    use parquet::{
+        arrow::arrow_reader::ParquetRecordBatchReaderBuilder, file::properties::WriterProperties,
+    };
+    use std::sync::Arc;
+
+    const ROW_GROUP_SIZE: usize = 100;
+
+    /// 1000 rows in row groups of 100: `id` counts from 0, `score` is half
+    /// of it with a NaN every 250 rows, and `flag` is set in the second half.
+    fn batch() -> RecordBatch {
+        let ids = (0..1000).collect::<Vec<i64>>();
+        let scores = ids
+            .iter()
+            .map(|&i| {
+                if i % 250 == 7 {
+                    f64::NAN
+                } else {
+                    i as f64 / 2.0
+                }
+            })
+            .collect::<Vec<_>>();
+        let flags = ids.iter().map(|&i| i >= 500).collect::<Vec<_>>();
+        RecordBatch::try_from_iter([
+            ("id", Arc::new(Int64Array::from(ids)) as ArrayRef),
+            ("score", Arc::new(Float64Array::from(scores)) as ArrayRef),
+            ("flag", Arc::new(BooleanArray::from(flags)) as ArrayRef),
+        ])
+        .unwrap()
+    }
+
+    /// Writes `batch` to a Parquet file, returning which row groups `expr`
+    /// keeps and how many rows of each it matches.
+    fn prune_and_evaluate(batch: &RecordBatch, expr: &str) -> Vec<(bool, usize)> {
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(ROW_GROUP_SIZE)
+            .build();
+        let reader =
+            ParquetRecordBatchReaderBuilder::try_new(testing::write(batch, props)).unwrap();
+        let expr = parse(expr).unwrap();
+        let keep = prune(
+            &expr,
+            &RowGroupStatistics::new(reader.metadata(), reader.schema()),
+        );
+        keep.into_iter()
+            .enumerate()
+            .map(|(i, keep)| {
+                let rows = batch.slice(i * ROW_GROUP_SIZE, ROW_GROUP_SIZE);
+                (keep, expr.evaluate(&rows).unwrap().true_count())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn never_prunes_matching_row_groups() {
+        let batch = batch();
+        for expr in [
+            "id",
+            "not id",
+            "score",
+            "not score",
+            "flag",
+            "not flag",
+            "not (flag or id < 10)",
+            "id > 250 and score",
+            "score > 400",
+            "score >= 400",
+            "score != 10",
+            "score not in (10, 20)",
+            "not (score <= 400)",
+            "score = 'NaN'",
+            "score < 'NaN'",
+            "score < 10",
+            "id = 5",
+            "id != 5",
+            "id in (5, 905)",
+        ] {
+            for (i, (keep, matches)) in prune_and_evaluate(&batch, expr).into_iter().enumerate() {
+                assert!(
+                    keep || matches == 0,
+                    "`{}` prunes row group {} with {} matching rows",
+                    expr,
+                    i,
+                    matches
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn prunes_row_groups_without_matches() {
+        let batch = batch();
+        for (expr, kept) in [
+            ("id < 150", 2),
+            ("id = 5", 1),
+            ("score < 10", 1),
+            ("flag", 5),
+            ("not flag", 5),
+            ("id is null", 0),
+        ] {
+            let result = prune_and_evaluate(&batch, expr);
+            let count = result.iter().filter(|(keep, _)| *keep).count();
+            assert_eq!(count, kept, "`{}`", expr);
+        }
+    }
+}
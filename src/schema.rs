@@ -0,0 +1,137 @@
+use parquet::{
+    basic::{ConvertedType, LogicalType, TimeUnit},
+    schema::types::{SchemaDescriptor, Type},
+};
+use std::io::{self, Write};
+
+/// Name of a logical type, with its parameters.
+pub fn logical_name(logical: &LogicalType) -> String {
+    let unit = |unit: &TimeUnit| match unit {
+        TimeUnit::MILLIS(_) => "MILLIS",
+        TimeUnit::MICROS(_) => "MICROS",
+        TimeUnit::NANOS(_) => "NANOS",
+    };
+    match logical {
+        LogicalType::String => "STRING".to_string(),
+        LogicalType::Map => "MAP".to_string(),
+        LogicalType::List => "LIST".to_string(),
+        LogicalType::Enum => "ENUM".to_string(),
+        LogicalType::Decimal { scale, precision } => format!("DECIMAL({}, {})", precision, scale),
+        LogicalType::Date => "DATE".to_string(),
+        LogicalType::Time {
+            is_adjusted_to_u_t_c,
+            unit: u,
+        } => format!("TIME({}, utc={})", unit(u), is_adjusted_to_u_t_c),
+        LogicalType::Timestamp {
+            is_adjusted_to_u_t_c,
+            unit: u,
+        } => format!("TIMESTAMP({}, utc={})", unit(u), is_adjusted_to_u_t_c),
+        LogicalType::Integer {
+            bit_width,
+            is_signed,
+        } => format!("INT({}, {})", bit_width, is_signed),
+        LogicalType::Unknown => "UNKNOWN".to_string(),
+        LogicalType::Json => "JSON".to_string(),
+        LogicalType::Bson => "BSON".to_string(),
+        LogicalType::Uuid => "UUID".to_string(),
+        LogicalType::Float16 => "FLOAT16".to_string(),
+    }
+}
+
+/// Prints the physical schema as a tree, keeping only the specified roots.
+pub fn print(schema: &SchemaDescriptor, roots: &[usize]) -> io::Result<()> {
+    let fields = schema.root_schema().get_fields();
+    // Leaves are numbered in depth-first order.
+    let mut offsets = Vec::with_capacity(fields.len());
+    let mut leaf = 0;
+    for field in fields {
+        offsets.push(leaf);
+        leaf += num_leaves(field);
+    }
+    let mut out = io::stdout().lock();
+    writeln!(out, "{}", schema.name())?;
+    for (i, &root) in roots.iter().enumerate() {
+        let mut printer = Printer {
+            out: &mut out,
+            schema,
+            leaf: offsets[root],
+        };
+        printer.print(&fields[root], "", i + 1 == roots.len())?;
+    }
+    Ok(())
+}
+
+fn num_leaves(ty: &Type) -> usize {
+    if ty.is_primitive() {
+        1
+    } else {
+        ty.get_fields().iter().map(|f| num_leaves(f)).sum()
+    }
+}
+
+struct Printer<'a, W: Write> {
+    out: &'a mut W,
+    schema: &'a SchemaDescriptor,
+    leaf: usize,
+}
+
+impl<W: Write> Printer<'_, W> {
+    fn print(&mut self, ty: &Type, prefix: &str, last: bool) -> io::Result<()> {
+        let info = ty.get_basic_info();
+        let mut attrs = vec![];
+        if info.has_repetition() {
+            attrs.push(info.repetition().to_string());
+        }
+        match ty {
+            Type::PrimitiveType {
+                physical_type,
+                type_length,
+                scale,
+                precision,
+                ..
+            } => {
+                attrs.push(physical_type.to_string());
+                if *type_length > 0 {
+                    attrs.push(format!("length={}", type_length));
+                }
+                if *precision > 0 {
+                    attrs.push(format!("precision={}", precision));
+                    attrs.push(format!("scale={}", scale));
+                }
+            }
+            Type::GroupType { .. } => attrs.push("group".to_string()),
+        }
+        if let Some(logical) = info.logical_type() {
+            attrs.push(format!("logical={}", logical_name(&logical)));
+        }
+        if info.converted_type() != ConvertedType::NONE {
+            attrs.push(format!("converted={}", info.converted_type()));
+        }
+        if info.has_id() {
+            attrs.push(format!("id={}", info.id()));
+        }
+        if ty.is_primitive() {
+            let column = self.schema.column(self.leaf);
+            attrs.push(format!("max_def={}", column.max_def_level()));
+            attrs.push(format!("max_rep={}", column.max_rep_level()));
+            self.leaf += 1;
+        }
+        let branch = if last { "└── " } else { "├── " };
+        writeln!(
+            self.out,
+            "{}{}{} {}",
+            prefix,
+            branch,
+            ty.name(),
+            attrs.join(" ")
+        )?;
+        let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        if ty.is_group() {
+            let fields = ty.get_fields();
+            for (i, field) in fields.iter().enumerate() {
+                self.print(field, &prefix, i + 1 == fields.len())?;
+            }
+        }
+        Ok(())
+    }
+}
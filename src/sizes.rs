@@ -0,0 +1,256 @@
+use crate::{
+    metadata::{codec_name, encoding_names},
+    output::table,
+};
+use comfy_table::Cell;
+use parquet::{basic::Encoding, file::metadata::ParquetMetaData};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+/// Storage used by a column, added up across row groups and files.
+#[derive(Default)]
+struct Size {
+    name: String,
+    compressed: u64,
+    uncompressed: u64,
+    encodings: BTreeSet<Encoding>,
+    codecs: BTreeSet<&'static str>,
+    /// The leaves of a nested column.
+    children: Vec<Size>,
+}
+
+impl Size {
+    fn add(&mut self, other: &Size) {
+        self.compressed += other.compressed;
+        self.uncompressed += other.uncompressed;
+        self.encodings.extend(&other.encodings);
+        self.codecs.extend(&other.codecs);
+    }
+
+    fn ratio(&self) -> f64 {
+        self.uncompressed as f64 / self.compressed.max(1) as f64
+    }
+
+    fn encodings(&self) -> String {
+        encoding_names(&self.encodings.iter().copied().collect::<Vec<_>>())
+    }
+
+    fn codecs(&self) -> String {
+        self.codecs.iter().copied().collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// The dataset totals the percentages and averages are based on.
+struct Totals {
+    bytes: u64,
+    rows: u64,
+}
+
+/// Adds up the sizes of the specified leaf columns by top-level column,
+/// largest first. `files` holds the metadata and length of each file.
+fn collect(files: &[(&ParquetMetaData, u64)], leaves: &[usize]) -> (Vec<Size>, Totals) {
+    let mut roots = vec![];
+    let mut sizes = vec![];
+    let mut totals = Totals { bytes: 0, rows: 0 };
+    for (metadata, len) in files {
+        totals.bytes += len;
+        totals.rows += metadata.file_metadata().num_rows() as u64;
+        let schema = metadata.file_metadata().schema_descr();
+        for rg in metadata.row_groups() {
+            for &j in leaves {
+                let c = rg.column(j);
+                let root = schema.get_column_root_idx(j);
+                let size = match roots.iter().position(|&r| r == root) {
+                    Some(i) => &mut sizes[i],
+                    None => {
+                        roots.push(root);
+                        sizes.push(Size::default());
+                        sizes.last_mut().unwrap()
+                    }
+                };
+                let path = c.column_path().string();
+                let leaf = Size {
+                    name: path.clone(),
+                    compressed: c.compressed_size() as u64,
+                    uncompressed: c.uncompressed_size() as u64,
+                    encodings: c.encodings().iter().copied().collect(),
+                    codecs: BTreeSet::from([codec_name(c.compression())]),
+                    children: vec![],
+                };
+                size.add(&leaf);
+                size.name = schema.root_schema().get_fields()[root].name().to_string();
+                if path != size.name {
+                    match size.children.iter_mut().find(|s| s.name == path) {
+                        Some(child) => child.add(&leaf),
+                        None => size.children.push(leaf),
+                    }
+                }
+            }
+        }
+    }
+    sizes.sort_by_key(|s| std::cmp::Reverse(s.compressed));
+    for size in &mut sizes {
+        size.children
+            .sort_by_key(|s| std::cmp::Reverse(s.compressed));
+    }
+    (sizes, totals)
+}
+
+/// Prints the storage size of the specified leaf columns.
+pub fn print(files: &[(&ParquetMetaData, u64)], leaves: &[usize], fit_width: Option<usize>) {
+    let (sizes, totals) = collect(files, leaves);
+    let row = |size: &Size, name: String| {
+        vec![
+            Cell::new(name),
+            Cell::new(size.compressed),
+            Cell::new(size.uncompressed),
+            Cell::new(format!(
+                "{:.1}%",
+                100.0 * size.compressed as f64 / totals.bytes.max(1) as f64
+            )),
+            Cell::new(format!("{:.2}", size.ratio())),
+            Cell::new(format!(
+                "{:.1}",
+                size.compressed as f64 / totals.rows.max(1) as f64
+            )),
+            Cell::new(size.encodings()),
+            Cell::new(size.codecs()),
+        ]
+    };
+    let mut rows = vec![];
+    for size in &sizes {
+        rows.push(row(size, size.name.clone()));
+        for child in &size.children {
+            rows.push(row(child, format!("  {}", child.name)));
+        }
+    }
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec![
+                "column",
+                "compressed",
+                "uncompressed",
+                "% of file",
+                "ratio",
+                "bytes per row",
+                "encodings",
+                "codecs",
+            ])
+            .add_rows(rows)
+    );
+}
+
+/// Converts the storage size of the specified leaf columns to JSON.
+pub fn to_json(files: &[(&ParquetMetaData, u64)], leaves: &[usize]) -> Value {
+    let (sizes, totals) = collect(files, leaves);
+    let to_json = |size: &Size| {
+        json!({
+            "column": size.name,
+            "compressed_size": size.compressed,
+            "uncompressed_size": size.uncompressed,
+            "percent_of_file": 100.0 * size.compressed as f64 / totals.bytes.max(1) as f64,
+            "compression_ratio": size.ratio(),
+            "bytes_per_row": size.compressed as f64 / totals.rows.max(1) as f64,
+            "encodings": size.encodings.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            "codecs": size.codecs,
+        })
+    };
+    Value::Array(
+        sizes
+            .iter()
+            .map(|size| {
+                let mut value = to_json(size);
+                if !size.children.is_empty() {
+                    value["leaves"] = size.children.iter().map(to_json).collect();
+                }
+                value
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray, StructArray};
+    use arrow_schema::{DataType, Field};
+    use parquet::{
+        arrow::arrow_reader::ParquetRecordBatchReaderBuilder, file::properties::WriterProperties,
+    };
+    use std::sync::Arc;
+
+    /// 300 rows of `id` and a struct `s` of `a` and `b`, in row groups of
+    /// 100.
+    fn metadata() -> Arc<ParquetMetaData> {
+        let a = Arc::new(Int64Array::from_iter_values(0..300)) as ArrayRef;
+        let b = Arc::new(StringArray::from_iter_values(
+            (0..300).map(|i| format!("a longer value {}", i)),
+        )) as ArrayRef;
+        let s = StructArray::from(vec![
+            (Arc::new(Field::new("a", DataType::Int64, false)), a.clone()),
+            (Arc::new(Field::new("b", DataType::Utf8, false)), b),
+        ]);
+        let batch =
+            RecordBatch::try_from_iter([("id", a), ("s", Arc::new(s) as ArrayRef)]).unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(100)
+            .build();
+        ParquetRecordBatchReaderBuilder::try_new(testing::write(&batch, props))
+            .unwrap()
+            .metadata()
+            .clone()
+    }
+
+    /// The compressed and uncompressed sizes of a leaf column in a file.
+    fn leaf_size(metadata: &ParquetMetaData, leaf: usize) -> (u64, u64) {
+        metadata.row_groups().iter().fold((0, 0), |(c, u), rg| {
+            let column = rg.column(leaf);
+            (
+                c + column.compressed_size() as u64,
+                u + column.uncompressed_size() as u64,
+            )
+        })
+    }
+
+    #[test]
+    fn adds_up_row_groups_and_files() {
+        let metadata = metadata();
+        assert_eq!(metadata.num_row_groups(), 3);
+        let files = [(metadata.as_ref(), 1000), (metadata.as_ref(), 2000)];
+        let (sizes, totals) = collect(&files, &[0, 1, 2]);
+        assert_eq!((totals.bytes, totals.rows), (3000, 600));
+        let twice = |leaf| {
+            let (c, u) = leaf_size(&metadata, leaf);
+            (2 * c, 2 * u)
+        };
+        let (a, b) = (twice(1), twice(2));
+        // The struct holds a longer string, so it comes first.
+        let names = sizes.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["s", "id"]);
+        let (s, id) = (&sizes[0], &sizes[1]);
+        assert_eq!((id.compressed, id.uncompressed), twice(0));
+        assert!(id.children.is_empty());
+        assert_eq!((s.compressed, s.uncompressed), (a.0 + b.0, a.1 + b.1));
+        let children = s
+            .children
+            .iter()
+            .map(|c| (c.name.as_str(), c.compressed, c.uncompressed))
+            .collect::<Vec<_>>();
+        assert_eq!(children, [("s.b", b.0, b.1), ("s.a", a.0, a.1)]);
+    }
+
+    #[test]
+    fn keeps_only_the_leaves_asked_for() {
+        let metadata = metadata();
+        let (sizes, _) = collect(&[(metadata.as_ref(), 0)], &[2]);
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes[0].name, "s");
+        assert_eq!(
+            (sizes[0].compressed, sizes[0].uncompressed),
+            leaf_size(&metadata, 2)
+        );
+        assert_eq!(sizes[0].children.len(), 1);
+    }
+}
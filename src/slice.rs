@@ -0,0 +1,179 @@
+use arrow_array::BooleanArray;
+use parquet::{
+    arrow::arrow_reader::{RowSelection, RowSelector},
+    file::metadata::ParquetMetaData,
+};
+use std::{collections::VecDeque, ops::Range};
+
+/// Selects the rows `skip..skip + take` of a file.
+///
+/// Returns the row groups overlapping the range, and the selection of rows
+/// within those row groups, so that the others are never read.
+pub fn select(metadata: &ParquetMetaData, skip: usize, take: usize) -> (Vec<usize>, RowSelection) {
+    let end = skip + take;
+    let mut row_groups = vec![];
+    let mut selectors = vec![];
+    let mut start = 0;
+    for (i, rg) in metadata.row_groups().iter().enumerate() {
+        let num_rows = rg.num_rows() as usize;
+        let rg_end = start + num_rows;
+        if rg_end > skip && start < end {
+            row_groups.push(i);
+            let first = skip.max(start) - start;
+            let last = end.min(rg_end) - start;
+            if first > 0 {
+                selectors.push(RowSelector::skip(first));
+            }
+            selectors.push(RowSelector::select(last - first));
+            if last < num_rows {
+                selectors.push(RowSelector::skip(num_rows - last));
+            }
+        }
+        start = rg_end;
+    }
+    (row_groups, selectors.into())
+}
+
+/// The index in the file of each row matched by a row filter, recorded as the
+/// filter is evaluated, to number the rows it lets through.
+///
+/// The reader evaluates the filter over the whole file before the first batch
+/// is read, so only the first `limit` matches are recorded, as no more are
+/// read.
+pub struct Matches {
+    /// The rows of each row group read, in order.
+    ranges: Vec<Range<usize>>,
+    /// The range holding the next row evaluated, and the number of rows
+    /// evaluated before it.
+    range: usize,
+    base: usize,
+    evaluated: usize,
+    rows: VecDeque<usize>,
+    /// The number of matches still to record.
+    limit: usize,
+}
+
+impl Matches {
+    pub fn new(metadata: &ParquetMetaData, row_groups: &[usize], limit: usize) -> Self {
+        let mut starts = vec![0];
+        for rg in metadata.row_groups() {
+            starts.push(starts[starts.len() - 1] + rg.num_rows() as usize);
+        }
+        Self {
+            ranges: row_groups
+                .iter()
+                .map(|&i| starts[i]..starts[i + 1])
+                .collect(),
+            range: 0,
+            base: 0,
+            evaluated: 0,
+            rows: VecDeque::new(),
+            limit,
+        }
+    }
+
+    /// Records the result of the filter for the next rows evaluated.
+    pub fn record(&mut self, mask: &BooleanArray) {
+        for (i, matched) in mask.iter().enumerate() {
+            if self.limit == 0 {
+                break;
+            }
+            if matched != Some(true) {
+                continue;
+            }
+            let position = self.evaluated + i;
+            while self.range + 1 < self.ranges.len()
+                && position >= self.base + self.ranges[self.range].len()
+            {
+                self.base += self.ranges[self.range].len();
+                self.range += 1;
+            }
+            let start = self.ranges.get(self.range).map_or(0, |r| r.start);
+            self.rows.push_back(start + position - self.base);
+            self.limit -= 1;
+        }
+        self.evaluated += mask.len();
+    }
+
+    /// The indices of the next `n` rows let through.
+    pub fn take(&mut self, n: usize) -> Vec<usize> {
+        self.rows.drain(..n.min(self.rows.len())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use arrow_array::{cast::AsArray, types::Int64Type, ArrayRef, Int64Array, RecordBatch};
+    use bytes::Bytes;
+    use parquet::{
+        arrow::arrow_reader::ParquetRecordBatchReaderBuilder, file::properties::WriterProperties,
+    };
+    use std::sync::Arc;
+
+    /// A file of 250 rows numbered from 0, in row groups of 100, 100 and 50.
+    fn file() -> Bytes {
+        let ids = Arc::new(Int64Array::from_iter_values(0..250)) as ArrayRef;
+        let batch = RecordBatch::try_from_iter([("id", ids)]).unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(100)
+            .build();
+        testing::write(&batch, props)
+    }
+
+    fn metadata() -> Arc<ParquetMetaData> {
+        ParquetRecordBatchReaderBuilder::try_new(file())
+            .unwrap()
+            .metadata()
+            .clone()
+    }
+
+    /// The row groups selected, and the ids of the rows read.
+    fn read(skip: usize, take: usize) -> (Vec<usize>, Vec<i64>) {
+        let (row_groups, selection) = select(&metadata(), skip, take);
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file())
+            .unwrap()
+            .with_row_groups(row_groups.clone())
+            .with_row_selection(selection)
+            .build()
+            .unwrap();
+        let mut ids = vec![];
+        for batch in reader {
+            let batch = batch.unwrap();
+            ids.extend(batch.column(0).as_primitive::<Int64Type>().values().iter());
+        }
+        (row_groups, ids)
+    }
+
+    #[test]
+    fn selects_rows_across_row_groups() {
+        assert_eq!(read(0, 10), (vec![0], (0..10).collect()));
+        assert_eq!(read(150, 100), (vec![1, 2], (150..250).collect()));
+        assert_eq!(read(99, 2), (vec![0, 1], vec![99, 100]));
+        assert_eq!(read(240, 100), (vec![2], (240..250).collect()));
+        assert_eq!(read(300, 10), (vec![], vec![]));
+    }
+
+    fn mask(len: usize, matched: &[usize]) -> BooleanArray {
+        (0..len).map(|i| Some(matched.contains(&i))).collect()
+    }
+
+    #[test]
+    fn numbers_matches_in_the_row_groups_read() {
+        // Row groups 0 and 2 hold rows 0..100 and 200..250.
+        let mut matches = Matches::new(&metadata(), &[0, 2], usize::MAX);
+        matches.record(&mask(60, &[0, 59]));
+        matches.record(&mask(90, &[39, 40, 89]));
+        assert_eq!(matches.take(2), [0, 59]);
+        assert_eq!(matches.take(10), [99, 200, 249]);
+        assert_eq!(matches.take(1), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn records_at_most_the_limit() {
+        let mut matches = Matches::new(&metadata(), &[1], 3);
+        matches.record(&mask(100, &[1, 2, 3, 4, 5]));
+        assert_eq!(matches.take(10), [101, 102, 103]);
+    }
+}
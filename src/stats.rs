@@ -0,0 +1,263 @@
+use crate::output::{optional, table};
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Decimal128Array, Float16Array, Float32Array, Float64Array,
+    Int32Array, Int64Array, StringArray, TimestampNanosecondArray, UInt32Array, UInt64Array,
+};
+use arrow_cast::{
+    cast,
+    display::{ArrayFormatter, FormatOptions},
+};
+use arrow_schema::DataType;
+use comfy_table::Cell;
+use half::f16;
+use parquet::{
+    arrow::parquet_to_arrow_schema,
+    data_type::Int96,
+    file::{metadata::ParquetMetaData, statistics::Statistics},
+    schema::types::{ColumnDescriptor, SchemaDescriptor, Type},
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// A statistics value, as stored in the file.
+pub enum Physical<'a> {
+    Bool(bool),
+    Int32(i32),
+    Int64(i64),
+    Int96(&'a Int96),
+    Float(f32),
+    Double(f64),
+    Bytes(&'a [u8]),
+}
+
+impl Physical<'_> {
+    /// Converts the value to a single element array of the logical type.
+    ///
+    /// Falls back to the physical type when the value can't be converted.
+    fn to_array(&self, data_type: &DataType) -> ArrayRef {
+        let convert = |raw: ArrayRef| cast(&raw, data_type).unwrap_or(raw);
+        match (self, data_type) {
+            (Self::Bool(v), _) => Arc::new(BooleanArray::from(vec![*v])),
+            (Self::Int32(v), DataType::UInt8 | DataType::UInt16 | DataType::UInt32) => {
+                convert(Arc::new(UInt32Array::from(vec![*v as u32])))
+            }
+            (Self::Int32(v), DataType::Decimal128(p, s)) => decimal(*v as i128, *p, *s),
+            (Self::Int32(v), _) => convert(Arc::new(Int32Array::from(vec![*v]))),
+            (Self::Int64(v), DataType::UInt64) => Arc::new(UInt64Array::from(vec![*v as u64])),
+            (Self::Int64(v), DataType::Decimal128(p, s)) => decimal(*v as i128, *p, *s),
+            (Self::Int64(v), _) => convert(Arc::new(Int64Array::from(vec![*v]))),
+            (Self::Int96(v), _) => {
+                convert(Arc::new(TimestampNanosecondArray::from(vec![v.to_nanos()])))
+            }
+            (Self::Float(v), _) => Arc::new(Float32Array::from(vec![*v])),
+            (Self::Double(v), _) => Arc::new(Float64Array::from(vec![*v])),
+            (Self::Bytes(v), DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View) => {
+                // Truncated statistics may end in the middle of a character.
+                Arc::new(StringArray::from(vec![
+                    String::from_utf8_lossy(v).into_owned()
+                ]))
+            }
+            (Self::Bytes(v), DataType::Decimal128(p, s)) if !v.is_empty() && v.len() <= 16 => {
+                let fill = if v[0] & 0x80 != 0 { 0xFF } else { 0 };
+                let mut bytes = [fill; 16];
+                bytes[16 - v.len()..].copy_from_slice(v);
+                decimal(i128::from_be_bytes(bytes), *p, *s)
+            }
+            (Self::Bytes(v), DataType::Float16) if v.len() == 2 => {
+                Arc::new(Float16Array::from(vec![f16::from_le_bytes([v[0], v[1]])]))
+            }
+            (Self::Bytes(v), _) => Arc::new(BinaryArray::from(vec![*v])),
+        }
+    }
+
+    /// Formats the value using the logical type.
+    pub fn format(&self, data_type: &DataType) -> String {
+        let array = self.to_array(data_type);
+        ArrayFormatter::try_new(&array, &FormatOptions::default())
+            .and_then(|f| f.value(0).try_to_string())
+            .unwrap_or_default()
+    }
+}
+
+fn decimal(value: i128, precision: u8, scale: i8) -> ArrayRef {
+    let array = Decimal128Array::from(vec![value]);
+    match array.clone().with_precision_and_scale(precision, scale) {
+        Ok(array) => Arc::new(array),
+        Err(_) => Arc::new(array),
+    }
+}
+
+/// The minimum and maximum values of the statistics, if set.
+pub fn min_max(stats: &Statistics) -> (Option<Physical<'_>>, Option<Physical<'_>>) {
+    match stats {
+        Statistics::Boolean(s) => (
+            s.min_opt().map(|v| Physical::Bool(*v)),
+            s.max_opt().map(|v| Physical::Bool(*v)),
+        ),
+        Statistics::Int32(s) => (
+            s.min_opt().map(|v| Physical::Int32(*v)),
+            s.max_opt().map(|v| Physical::Int32(*v)),
+        ),
+        Statistics::Int64(s) => (
+            s.min_opt().map(|v| Physical::Int64(*v)),
+            s.max_opt().map(|v| Physical::Int64(*v)),
+        ),
+        Statistics::Int96(s) => (
+            s.min_opt().map(Physical::Int96),
+            s.max_opt().map(Physical::Int96),
+        ),
+        Statistics::Float(s) => (
+            s.min_opt().map(|v| Physical::Float(*v)),
+            s.max_opt().map(|v| Physical::Float(*v)),
+        ),
+        Statistics::Double(s) => (
+            s.min_opt().map(|v| Physical::Double(*v)),
+            s.max_opt().map(|v| Physical::Double(*v)),
+        ),
+        Statistics::ByteArray(s) => (
+            s.min_opt().map(|v| Physical::Bytes(v.data())),
+            s.max_opt().map(|v| Physical::Bytes(v.data())),
+        ),
+        Statistics::FixedLenByteArray(s) => (
+            s.min_opt().map(|v| Physical::Bytes(v.data())),
+            s.max_opt().map(|v| Physical::Bytes(v.data())),
+        ),
+    }
+}
+
+/// The Arrow type of a leaf column, used to decode its values.
+pub fn leaf_type(descr: &ColumnDescriptor) -> DataType {
+    let root = Type::group_type_builder("schema")
+        .with_fields(vec![descr.self_type_ptr()])
+        .build();
+    let schema =
+        root.and_then(|root| parquet_to_arrow_schema(&SchemaDescriptor::new(Arc::new(root)), None));
+    match schema.as_ref().map(|s| s.field(0).data_type()) {
+        // A repeated leaf is read as a list of its values.
+        Ok(DataType::List(f)) => f.data_type().clone(),
+        Ok(t) => t.clone(),
+        Err(_) => DataType::Null,
+    }
+}
+
+/// Decoded statistics of a column chunk.
+struct ChunkStatistics {
+    min: Option<String>,
+    max: Option<String>,
+    null_count: Option<u64>,
+    distinct_count: Option<u64>,
+    min_is_exact: bool,
+    max_is_exact: bool,
+}
+
+/// Decodes the statistics of the specified leaf columns.
+///
+/// Column chunks without statistics are `None`.
+fn collect(
+    metadata: &ParquetMetaData,
+    leaves: &[usize],
+) -> Vec<(usize, String, DataType, Option<ChunkStatistics>)> {
+    let mut result = vec![];
+    for (i, rg) in metadata.row_groups().iter().enumerate() {
+        for c in leaves.iter().map(|&j| rg.column(j)) {
+            let data_type = leaf_type(c.column_descr());
+            let stats = c.statistics().map(|s| {
+                let (min, max) = min_max(s);
+                ChunkStatistics {
+                    min: min.map(|v| v.format(&data_type)),
+                    max: max.map(|v| v.format(&data_type)),
+                    null_count: s.null_count_opt(),
+                    distinct_count: s.distinct_count_opt(),
+                    min_is_exact: s.min_is_exact(),
+                    max_is_exact: s.max_is_exact(),
+                }
+            });
+            result.push((i, c.column_path().string(), data_type, stats));
+        }
+    }
+    result
+}
+
+/// Prints the statistics of the specified leaf columns.
+pub fn print(metadata: &ParquetMetaData, leaves: &[usize], fit_width: Option<usize>) {
+    let rows =
+        collect(metadata, leaves)
+            .into_iter()
+            .map(|(row_group, column, data_type, stats)| match stats {
+                Some(s) => vec![
+                    Cell::new(row_group),
+                    Cell::new(column),
+                    Cell::new(data_type),
+                    Cell::new(optional(s.min)),
+                    Cell::new(optional(s.max)),
+                    Cell::new(optional(s.null_count)),
+                    Cell::new(optional(s.distinct_count)),
+                    Cell::new(s.min_is_exact),
+                    Cell::new(s.max_is_exact),
+                ],
+                None => vec![
+                    Cell::new(row_group),
+                    Cell::new(column),
+                    Cell::new(data_type),
+                    Cell::new("no statistics"),
+                ],
+            });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec![
+                "row group",
+                "column",
+                "type",
+                "min",
+                "max",
+                "nulls",
+                "distinct",
+                "min exact",
+                "max exact",
+            ])
+            .add_rows(rows)
+    );
+}
+
+/// Converts the statistics of the specified leaf columns to JSON.
+pub fn to_json(metadata: &ParquetMetaData, leaves: &[usize]) -> Value {
+    let columns = collect(metadata, leaves)
+        .into_iter()
+        .map(|(row_group, column, data_type, stats)| match stats {
+            Some(s) => json!({
+                "row_group": row_group,
+                "column": column,
+                "type": data_type.to_string(),
+                "min": s.min,
+                "max": s.max,
+                "null_count": s.null_count,
+                "distinct_count": s.distinct_count,
+                "min_is_exact": s.min_is_exact,
+                "max_is_exact": s.max_is_exact,
+            }),
+            None => json!({
+                "row_group": row_group,
+                "column": column,
+                "type": data_type.to_string(),
+            }),
+        })
+        .collect::<Vec<_>>();
+    Value::Array(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_float16_values() {
+        let bytes = f16::from_f32(-1.5).to_le_bytes();
+        assert_eq!(Physical::Bytes(&bytes).format(&DataType::Float16), "-1.5");
+        // Anything else than 2 bytes isn't a half float.
+        assert_eq!(
+            Physical::Bytes(&[1, 2, 3]).format(&DataType::Float16),
+            "010203"
+        );
+    }
+}
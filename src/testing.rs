@@ -0,0 +1,14 @@
+//! Helpers shared by the tests.
+
+use arrow_array::RecordBatch;
+use bytes::Bytes;
+use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+
+/// Writes `batch` to a Parquet file in memory.
+pub fn write(batch: &RecordBatch, props: WriterProperties) -> Bytes {
+    let mut buffer = vec![];
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(props)).unwrap();
+    writer.write(batch).unwrap();
+    writer.close().unwrap();
+    buffer.into()
+}
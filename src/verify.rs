@@ -0,0 +1,589 @@
+use crate::{
+    input::{chunk_start, Input},
+    output::{optional, table},
+};
+use comfy_table::Cell;
+use parquet::{
+    basic::PageType,
+    column::{
+        page::{Page, PageMetadata, PageReader},
+        reader::{get_column_reader, ColumnReader},
+    },
+    errors::{ParquetError, Result},
+    file::{
+        footer::{decode_footer, parse_metadata},
+        metadata::ColumnChunkMetaData,
+        reader::{ChunkReader, Length},
+        serialized_reader::SerializedPageReader,
+    },
+    schema::types::ColumnDescPtr,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    ffi::OsStr,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+/// Number of records decoded at a time.
+const BATCH_SIZE: usize = 1024;
+
+/// A problem found in a file, located as precisely as possible.
+pub struct Problem {
+    row_group: Option<usize>,
+    column: Option<String>,
+    /// Position of the page in its column chunk, the dictionary page
+    /// included.
+    page: Option<usize>,
+    offset: Option<u64>,
+    message: String,
+}
+
+impl Problem {
+    fn file(message: String) -> Self {
+        Self {
+            row_group: None,
+            column: None,
+            page: None,
+            offset: None,
+            message,
+        }
+    }
+}
+
+/// The message of an error, without the generic prefix.
+fn message(e: ParquetError) -> String {
+    match e {
+        ParquetError::General(message) => message,
+        e => e.to_string(),
+    }
+}
+
+/// Checks the structure of the file at `path` and decodes all of its pages,
+/// returning every problem found.
+pub fn check(path: &OsStr, spool_size: usize) -> Vec<Problem> {
+    let mut problems = vec![];
+    // Opening checks the magic bytes.
+    let result =
+        Input::open(Some(path), spool_size).and_then(|input| check_input(input, &mut problems));
+    if let Err(e) = result {
+        problems.push(Problem::file(message(e)));
+    }
+    problems
+}
+
+fn check_input(input: Input, problems: &mut Vec<Problem>) -> Result<()> {
+    let len = input.len();
+    let mut footer = [0; 8];
+    footer.copy_from_slice(&input.get_bytes(len - 8, 8)?);
+    let metadata_len = decode_footer(&footer)? as u64;
+    if metadata_len + 12 > len {
+        return Err(ParquetError::General(format!(
+            "footer length {} exceeds the file size of {} bytes",
+            metadata_len, len
+        )));
+    }
+    let metadata = parse_metadata(&input)
+        .map_err(|e| ParquetError::General(format!("invalid footer: {}", e)))?;
+    let declared = metadata.file_metadata().num_rows();
+    let total: i64 = metadata.row_groups().iter().map(|rg| rg.num_rows()).sum();
+    if declared != total {
+        problems.push(Problem::file(format!(
+            "the file declares {} rows but its row groups hold {}",
+            declared, total
+        )));
+    }
+    let schema = metadata.file_metadata().schema_descr();
+    let input = Arc::new(input);
+    for (i, rg) in metadata.row_groups().iter().enumerate() {
+        for (j, chunk) in rg.columns().iter().enumerate() {
+            problems.extend(check_chunk(
+                &input,
+                schema.column(j),
+                chunk,
+                i,
+                rg.num_rows().max(0) as usize,
+                len - 8 - metadata_len,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a column chunk, which must end before `data_end`: the bounds and
+/// checksums of its pages, then their contents.
+fn check_chunk(
+    input: &Arc<Input>,
+    descr: ColumnDescPtr,
+    chunk: &ColumnChunkMetaData,
+    row_group: usize,
+    num_rows: usize,
+    data_end: u64,
+) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut report = |page: Option<usize>, offset: Option<u64>, message: String| {
+        problems.push(Problem {
+            row_group: Some(row_group),
+            column: Some(chunk.column_path().string()),
+            page,
+            offset,
+            message,
+        })
+    };
+    let start = chunk_start(chunk);
+    let len = chunk.compressed_size();
+    if start < 4 || len < 0 || (start + len) as u64 > data_end {
+        report(
+            None,
+            None,
+            format!(
+                "column chunk of {} bytes at offset {} is out of bounds",
+                len, start
+            ),
+        );
+        return problems;
+    }
+    let (start, end) = (start as u64, (start + len) as u64);
+
+    // Walk the page headers first, to check the checksums of the raw pages.
+    let mut offsets = vec![];
+    let mut headers = input.page_headers(start, end);
+    loop {
+        let index = offsets.len();
+        let offset = headers.offset();
+        let page = match headers.next() {
+            Some(Ok(page)) => page,
+            Some(Err(e)) => {
+                report(Some(index), Some(offset), message(e));
+                return problems;
+            }
+            None => break,
+        };
+        offsets.push(page.offset);
+        let size = page.end() - page.data;
+        if page.end() > end {
+            report(
+                Some(index),
+                Some(offset),
+                format!(
+                    "page of {} bytes extends past the end of the column chunk",
+                    size
+                ),
+            );
+            return problems;
+        }
+        if let Some(crc) = page.header.crc {
+            match input.get_bytes(page.data, size as usize) {
+                Ok(bytes) => {
+                    let actual = crc32fast::hash(&bytes);
+                    if actual != crc as u32 {
+                        report(
+                            Some(index),
+                            Some(offset),
+                            format!(
+                                "checksum mismatch: expected {:08x}, found {:08x}",
+                                crc as u32, actual
+                            ),
+                        );
+                    }
+                }
+                Err(e) => report(Some(index), Some(offset), message(e)),
+            }
+        }
+    }
+
+    // Then decode each data page on its own, so that a corrupt page doesn't
+    // hide problems in the following ones.
+    let mut reader = match SerializedPageReader::new(input.clone(), chunk, num_rows, None) {
+        Ok(reader) => reader,
+        Err(e) => {
+            report(None, None, message(e));
+            return problems;
+        }
+    };
+    let mut dictionary = None;
+    let (mut rows, mut values, mut failed) = (0, 0, false);
+    for page in 0.. {
+        let offset = offsets.get(page).copied();
+        let next = match catch(|| reader.get_next_page()) {
+            Ok(Some(next)) => next,
+            Ok(None) => break,
+            Err(e) => {
+                report(Some(page), offset, message(e));
+                return problems;
+            }
+        };
+        if next.page_type() == PageType::DICTIONARY_PAGE {
+            dictionary = Some(next);
+            continue;
+        }
+        let pages = dictionary.iter().cloned().chain([next]).collect();
+        match catch(|| decode(descr.clone(), pages)) {
+            Ok((r, v)) => {
+                rows += r;
+                values += v;
+            }
+            Err(e) => {
+                report(Some(page), offset, message(e));
+                failed = true;
+            }
+        }
+    }
+    if !failed && rows != num_rows {
+        report(
+            None,
+            None,
+            format!("decoded {} rows, expected {}", rows, num_rows),
+        );
+    }
+    if !failed && values as i64 != chunk.num_values() {
+        report(
+            None,
+            None,
+            format!("decoded {} values, expected {}", values, chunk.num_values()),
+        );
+    }
+    problems
+}
+
+/// Runs `f`, turning its panics into errors. Decoders panic on some corrupt
+/// data; the panics are reported as problems, so the default message is
+/// silenced meanwhile.
+fn catch<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    panic::set_hook(hook);
+    result.unwrap_or_else(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(ParquetError::General(format!(
+            "decoding failed: {}",
+            reason
+        )))
+    })
+}
+
+/// Decodes `pages`, returning the number of records starting in them and the
+/// number of values read. A record of a repeated column can span pages, so
+/// its records are counted by their first value, with a repetition level of
+/// 0.
+fn decode(descr: ColumnDescPtr, pages: VecDeque<Page>) -> Result<(usize, usize)> {
+    let repeated = descr.max_rep_level() > 0;
+    macro_rules! read {
+        ($reader:expr) => {{
+            let mut reader = $reader;
+            let (mut values, mut def, mut rep) = (vec![], vec![], vec![]);
+            let (mut records, mut levels) = (0, 0);
+            loop {
+                let (r, _, l) =
+                    reader.read_records(BATCH_SIZE, Some(&mut def), Some(&mut rep), &mut values)?;
+                if r == 0 && l == 0 {
+                    break;
+                }
+                records += if repeated {
+                    rep.iter().filter(|&&level| level == 0).count()
+                } else {
+                    r
+                };
+                levels += l;
+                values.clear();
+                def.clear();
+                rep.clear();
+            }
+            Ok((records, levels))
+        }};
+    }
+    match get_column_reader(descr, Box::new(Pages(pages))) {
+        ColumnReader::BoolColumnReader(r) => read!(r),
+        ColumnReader::Int32ColumnReader(r) => read!(r),
+        ColumnReader::Int64ColumnReader(r) => read!(r),
+        ColumnReader::Int96ColumnReader(r) => read!(r),
+        ColumnReader::FloatColumnReader(r) => read!(r),
+        ColumnReader::DoubleColumnReader(r) => read!(r),
+        ColumnReader::ByteArrayColumnReader(r) => read!(r),
+        ColumnReader::FixedLenByteArrayColumnReader(r) => read!(r),
+    }
+}
+
+/// Pages already read, handed to a column reader.
+struct Pages(VecDeque<Page>);
+
+impl Iterator for Pages {
+    type Item = Result<Page>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front().map(Ok)
+    }
+}
+
+impl PageReader for Pages {
+    fn get_next_page(&mut self) -> Result<Option<Page>> {
+        Ok(self.0.pop_front())
+    }
+
+    fn peek_next_page(&mut self) -> Result<Option<PageMetadata>> {
+        Err(ParquetError::NYI("peeking at decoded pages".to_string()))
+    }
+
+    fn skip_next_page(&mut self) -> Result<()> {
+        self.0.pop_front();
+        Ok(())
+    }
+
+    fn at_record_boundary(&mut self) -> Result<bool> {
+        Ok(self.0.is_empty())
+    }
+}
+
+/// Prints the problems found in a file.
+pub fn print(problems: &[Problem], fit_width: Option<usize>) {
+    if problems.is_empty() {
+        println!("no problems found");
+        return;
+    }
+    let rows = problems.iter().map(|p| {
+        vec![
+            Cell::new(optional(p.row_group)),
+            Cell::new(optional(p.column.as_ref())),
+            Cell::new(optional(p.page)),
+            Cell::new(optional(p.offset)),
+            Cell::new(&p.message),
+        ]
+    });
+    println!(
+        "{}",
+        table(fit_width)
+            .set_header(vec!["row group", "column", "page", "offset", "problem"])
+            .add_rows(rows)
+    );
+}
+
+/// Converts the problems found in a file to JSON.
+pub fn to_json(problems: &[Problem]) -> Value {
+    problems
+        .iter()
+        .map(|p| {
+            json!({
+                "row_group": p.row_group,
+                "column": p.column,
+                "page": p.page,
+                "offset": p.offset,
+                "problem": p.message,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+    use bytes::Bytes;
+    use parquet::{
+        file::properties::WriterProperties,
+        format::{FileMetaData, PageHeader},
+        thrift::TSerializable,
+    };
+    use thrift::protocol::{TCompactOutputProtocol, TOutputProtocol};
+
+    /// 200 rows of `id` and `name`, in row groups of 100 rows and pages of
+    /// 40, without dictionaries.
+    fn file() -> Vec<u8> {
+        let ids = Int64Array::from_iter_values(0..200);
+        let names = StringArray::from_iter_values((0..200).map(|i| format!("name {}", i)));
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(ids) as ArrayRef),
+            ("name", Arc::new(names) as ArrayRef),
+        ])
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(100)
+            .set_data_page_row_count_limit(40)
+            .set_write_batch_size(40)
+            .set_dictionary_enabled(false)
+            .build();
+        testing::write(&batch, props).to_vec()
+    }
+
+    fn problems(file: Vec<u8>) -> Vec<Problem> {
+        let mut problems = vec![];
+        if let Err(e) = check_input(Input::Memory(Bytes::from(file)), &mut problems) {
+            problems.push(Problem::file(message(e)));
+        }
+        problems
+    }
+
+    fn serialize(value: &impl TSerializable) -> Vec<u8> {
+        let mut buffer = vec![];
+        let mut protocol = TCompactOutputProtocol::new(&mut buffer);
+        value.write_to_out_protocol(&mut protocol).unwrap();
+        protocol.flush().unwrap();
+        buffer
+    }
+
+    /// The footer of `file`, and where it starts.
+    fn footer(file: &[u8]) -> (FileMetaData, usize) {
+        let len = file.len();
+        let metadata_len = u32::from_le_bytes(file[len - 8..len - 4].try_into().unwrap());
+        let start = len - 8 - metadata_len as usize;
+        let input = Input::Memory(Bytes::copy_from_slice(file));
+        (input.read_thrift(start as u64, "footer").unwrap().0, start)
+    }
+
+    /// Replaces the footer of `file` with `metadata`.
+    fn with_footer(mut file: Vec<u8>, metadata: &FileMetaData) -> Vec<u8> {
+        let (_, start) = footer(&file);
+        let metadata = serialize(metadata);
+        file.truncate(start);
+        file.extend(&metadata);
+        file.extend((metadata.len() as u32).to_le_bytes());
+        file.extend(b"PAR1");
+        file
+    }
+
+    /// The offsets of the page headers of a column chunk.
+    fn pages(file: &[u8], row_group: usize, column: usize) -> Vec<usize> {
+        let (metadata, _) = footer(file);
+        let chunk = metadata.row_groups[row_group].columns[column]
+            .meta_data
+            .clone()
+            .unwrap();
+        let input = Input::Memory(Bytes::copy_from_slice(file));
+        let start = chunk.data_page_offset as u64;
+        let end = start + chunk.total_compressed_size as u64;
+        input
+            .page_headers(start, end)
+            .map(|page| page.unwrap().offset as usize)
+            .collect()
+    }
+
+    /// Sets the checksum of the first page of the first column chunk,
+    /// computed by `crc` from the page data.
+    fn with_crc(file: Vec<u8>, crc: impl FnOnce(&[u8]) -> u32) -> Vec<u8> {
+        let start = pages(&file, 0, 0)[0];
+        let input = Input::Memory(Bytes::copy_from_slice(&file));
+        let (mut header, len) = input
+            .read_thrift::<PageHeader>(start as u64, "page header")
+            .unwrap();
+        let data = start + len as usize;
+        let size = header.compressed_page_size as usize;
+        header.crc = Some(crc(&file[data..data + size]) as i32);
+        let header = serialize(&header);
+        let shift = header.len() as i64 - len as i64;
+        let (mut metadata, _) = footer(&file);
+        // Move everything after the header, which is only referred to by the
+        // row groups in the footer.
+        for (i, rg) in metadata.row_groups.iter_mut().enumerate() {
+            for (j, column) in rg.columns.iter_mut().enumerate() {
+                column.column_index_offset = None;
+                column.offset_index_offset = None;
+                let chunk = column.meta_data.as_mut().unwrap();
+                if (i, j) == (0, 0) {
+                    chunk.total_compressed_size += shift;
+                } else {
+                    chunk.data_page_offset += shift;
+                }
+            }
+        }
+        let mut patched = file[..start].to_vec();
+        patched.extend(header);
+        patched.extend(&file[data..]);
+        with_footer(patched, &metadata)
+    }
+
+    #[test]
+    fn finds_no_problems_in_a_valid_file() {
+        assert!(problems(file()).is_empty());
+        assert!(problems(with_crc(file(), crc32fast::hash)).is_empty());
+    }
+
+    #[test]
+    fn locates_a_corrupt_page_header() {
+        let mut file = file();
+        let offset = pages(&file, 1, 1)[1];
+        file[offset] = 0xff;
+        let problems = problems(file);
+        assert_eq!(problems.len(), 1);
+        let problem = &problems[0];
+        assert_eq!(problem.row_group, Some(1));
+        assert_eq!(problem.column.as_deref(), Some("name"));
+        assert_eq!(problem.page, Some(1));
+        assert_eq!(problem.offset, Some(offset as u64));
+        assert!(
+            problem
+                .message
+                .starts_with(&format!("invalid page header at offset {}", offset)),
+            "{}",
+            problem.message
+        );
+    }
+
+    #[test]
+    fn reports_chunks_out_of_bounds() {
+        let file = file();
+        let (mut metadata, _) = footer(&file);
+        let chunk = metadata.row_groups[0].columns[1]
+            .meta_data
+            .as_mut()
+            .unwrap();
+        chunk.total_compressed_size += file.len() as i64;
+        let problems = problems(with_footer(file, &metadata));
+        assert_eq!(problems.len(), 1);
+        let problem = &problems[0];
+        assert_eq!(problem.row_group, Some(0));
+        assert_eq!(problem.column.as_deref(), Some("name"));
+        assert_eq!(problem.page, None);
+        assert!(
+            problem.message.ends_with("is out of bounds"),
+            "{}",
+            problem.message
+        );
+    }
+
+    #[test]
+    fn reports_pages_past_their_chunk() {
+        let file = file();
+        let last = *pages(&file, 1, 0).last().unwrap();
+        let (mut metadata, _) = footer(&file);
+        let chunk = metadata.row_groups[1].columns[0]
+            .meta_data
+            .as_mut()
+            .unwrap();
+        chunk.total_compressed_size -= 1;
+        let problems = problems(with_footer(file, &metadata));
+        let problem = &problems[0];
+        assert_eq!(problem.row_group, Some(1));
+        assert_eq!(problem.column.as_deref(), Some("id"));
+        assert_eq!(problem.page, Some(2));
+        assert_eq!(problem.offset, Some(last as u64));
+        assert!(
+            problem.message.contains("extends past the end"),
+            "{}",
+            problem.message
+        );
+    }
+
+    #[test]
+    fn reports_checksum_mismatches() {
+        let file = with_crc(file(), |data| crc32fast::hash(data) ^ 1);
+        let offset = pages(&file, 0, 0)[0];
+        let problems = problems(file);
+        assert_eq!(problems.len(), 1);
+        let problem = &problems[0];
+        assert_eq!(problem.row_group, Some(0));
+        assert_eq!(problem.column.as_deref(), Some("id"));
+        assert_eq!(problem.page, Some(0));
+        assert_eq!(problem.offset, Some(offset as u64));
+        assert!(
+            problem.message.starts_with("checksum mismatch"),
+            "{}",
+            problem.message
+        );
+    }
+}